@@ -0,0 +1,36 @@
+//! Feeds arbitrary `rows;cols` hint strings to the parser and, if they parse, to a bounded run of
+//! the solver. Run it with `cargo fuzz run solve` from the `fuzz` directory.
+//!
+//! The solver isn't a library, so this file is compiled as a module of the binary when built
+//! with `--cfg fuzzing`, which also swaps out `main` for the fuzzer's entry point.
+use crate::{make_hints, Picross};
+use core::ops::ControlFlow;
+
+/// Lines much longer than this can have too many permutations to generate per input.
+const MAX_SIDE: usize = 12;
+const MAX_BACKTRACKS: usize = 64;
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| {
+    let Ok(input) = core::str::from_utf8(data) else {
+        return;
+    };
+    let Some((rows, cols)) = input.split_once(';') else {
+        return;
+    };
+    let (Ok(row_hints), Ok(col_hints)) = (make_hints(rows), make_hints(cols)) else {
+        return;
+    };
+    let (row_hints, col_hints) = (row_hints.get(), col_hints.get());
+    if row_hints.len() > MAX_SIDE || col_hints.len() > MAX_SIDE {
+        return;
+    }
+    let mut picross = Picross::new(row_hints, col_hints);
+    let _ = picross.search(|picross| {
+        if picross.num_backtracks > MAX_BACKTRACKS {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    });
+    let _ = picross.explain_contradiction();
+});
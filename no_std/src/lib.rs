@@ -0,0 +1,14 @@
+//! The `no_std` parts of the solver, built from the binary's own sources. See `Cargo.toml`.
+#![no_std]
+#![allow(dead_code)]
+
+extern crate alloc;
+
+#[path = "../../src/board.rs"]
+mod board;
+#[path = "../../src/cell.rs"]
+mod cell;
+#[path = "../../src/event.rs"]
+mod event;
+#[path = "../../src/hint.rs"]
+mod hint;
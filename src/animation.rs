@@ -0,0 +1,108 @@
+use crate::cell::Cell;
+use crate::hint::Hint;
+use crate::{GuessBoard, Picross};
+use gif::{Encoder, Frame, Repeat};
+use std::borrow::Cow;
+
+/// The most frames recorded before the final one, so a puzzle that needs many passes or guesses
+/// still makes a reasonably sized file.
+const MAX_FRAMES: usize = 500;
+/// The side of each cell in pixels, unless the board is too large for it.
+const CELL_SIZE: usize = 8;
+
+const FILLED: u8 = 0;
+const EMPTY: u8 = 1;
+const UNKNOWN: u8 = 2;
+const PALETTE: [u8; 9] = [0x20, 0x20, 0x20, 0xf0, 0xf0, 0xf0, 0x90, 0x90, 0x90];
+
+const fn color(x: Option<Cell>) -> u8 {
+    match x {
+        Some(true) => FILLED,
+        Some(false) => EMPTY,
+        None => UNKNOWN,
+    }
+}
+
+/// Solves the puzzle and encodes the board after every propagation pass as a looping animated
+/// GIF, with `frame_delay_ms` between frames. The last frame is the solution, if there is one.
+///
+/// # Panics
+///
+/// Panics if the puzzle is more than 65535 cells wide or tall.
+pub fn solve_to_gif(rows: &[Hint<'_>], cols: &[Hint<'_>], frame_delay_ms: u16) -> Vec<u8> {
+    let mut picross = Picross::new(rows, cols);
+    let mut frames: Vec<GuessBoard> = vec![];
+    let solution = picross.find_solution_with(|p| {
+        if frames.len() < MAX_FRAMES {
+            frames.push(p.board.clone());
+        }
+    });
+    if let Some(solution) = solution {
+        frames.push(GuessBoard::new_from_fn(
+            solution.width(),
+            solution.height(),
+            |x, y| Some(solution.row(y)[x]),
+        ));
+    }
+    encode(&frames, picross.width(), picross.height(), frame_delay_ms)
+}
+
+fn encode(frames: &[GuessBoard], width: usize, height: usize, frame_delay_ms: u16) -> Vec<u8> {
+    let scale = CELL_SIZE
+        .min(usize::from(u16::MAX) / width.max(height).max(1))
+        .max(1);
+    let dimension = |cells: usize| {
+        u16::try_from(cells * scale).expect("puzzle is too large to encode as a GIF")
+    };
+    let (pixel_width, pixel_height) = (dimension(width), dimension(height));
+    let mut bytes = vec![];
+    // Writing into a `Vec` can't fail, and the dimensions were checked above.
+    let mut encoder = Encoder::new(&mut bytes, pixel_width, pixel_height, &PALETTE).unwrap();
+    encoder.set_repeat(Repeat::Infinite).unwrap();
+    for board in frames {
+        let buffer: Vec<u8> = (0..height * scale)
+            .flat_map(|py| {
+                let row = board.row(py / scale);
+                (0..width * scale).map(move |px| color(row[px / scale]))
+            })
+            .collect();
+        let frame = Frame {
+            width: pixel_width,
+            height: pixel_height,
+            // GIF delays are in hundredths of a second.
+            delay: frame_delay_ms / 10,
+            buffer: Cow::Owned(buffer),
+            ..Frame::default()
+        };
+        encoder.write_frame(&frame).unwrap();
+    }
+    drop(encoder);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_hints;
+
+    #[test]
+    fn animation() {
+        let row_hints = make_hints("3, 1, 1").unwrap();
+        let col_hints = make_hints("1, 3, 1").unwrap();
+        let bytes = solve_to_gif(row_hints.get(), col_hints.get(), 100);
+        assert!(bytes.starts_with(b"GIF89a"));
+        assert_eq!(bytes.last(), Some(&0x3b));
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(bytes.as_slice())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (24, 24));
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            frames += 1;
+        }
+        // One propagation pass solves it, then the solution is appended.
+        assert_eq!(frames, 2);
+    }
+}
@@ -1,7 +1,77 @@
-use std::fmt;
-use std::ptr::NonNull;
-use std::slice;
+use crate::cell::Cell;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ptr::NonNull;
+use core::slice;
 
+/// A line given to the board doesn't have as many cells as the line it replaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthMismatch {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line has {} cells but the board expects {}",
+            self.actual, self.expected
+        )
+    }
+}
+
+/// Two boards that had to be the same size aren't, given as `(width, height)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub expected: (usize, usize),
+    pub actual: (usize, usize),
+}
+
+impl fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "board is {}x{} but should be {}x{}",
+            self.actual.0, self.actual.1, self.expected.0, self.expected.1
+        )
+    }
+}
+
+/// Which cells count as touching in [`Board::neighbors_with`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Adjacency {
+    /// Cells sharing an edge.
+    #[default]
+    Orthogonal,
+    /// Cells sharing an edge or a corner.
+    EightWay,
+}
+
+/// A row-major grid of cells backed by a single boxed slice.
+///
+/// # Panic safety
+///
+/// Every path that can run user code (`T::clone`, `T::drop`, or a constructor closure) leaves the
+/// board fully initialized if that code panics:
+///
+/// - Constructors collect into a `Vec` before taking ownership of the allocation, so a panic
+///   drops the cells produced so far and the board is never created.
+/// - `set_row`, `set_col` and friends assign cells one at a time. A panic while dropping an old
+///   cell still stores its replacement, and the rest of the incoming cells are dropped by their
+///   iterator, so the board ends up with a mix of old and new cells but no holes.
+/// - `Drop` rebuilds the original `Box<[T]>`, which keeps dropping the remaining cells and frees
+///   the allocation even if one of the drops panics.
+///
+/// # Memory
+///
+/// A board's memory is its cells: the header is a pointer and two `usize` dimensions no matter
+/// how large the board is, and an `Option<Cell>` cell takes a single byte. Narrower index types
+/// would only shrink the header.
 pub struct Board<T> {
     ptr: NonNull<T>,
     width: usize,
@@ -22,7 +92,37 @@ impl<T> Board<T> {
         let vec = (0..width * height).map(|_| f()).collect();
         unsafe { Self::from_vec(vec, width, height) }
     }
+    /// Builds a board from its cells in row-major order. Unlike [`Board::new_from_fn`], this
+    /// takes time proportional to the number of cells even when one side is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there aren't exactly `width * height` cells.
+    pub fn from_row_major(width: usize, height: usize, cells: Vec<T>) -> Self {
+        unsafe { Self::from_vec(cells, width, height) }
+    }
+    /// Builds a board by calling `f(x, y)` for every position in row-major order.
+    pub fn new_from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let vec = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        unsafe { Self::from_vec(vec, width, height) }
+    }
     unsafe fn from_vec(vec: Vec<T>, width: usize, height: usize) -> Self {
+        // Every other method trusts these dimensions to describe the allocation, so a wrapped
+        // `width * height` must never get this far.
+        assert_eq!(
+            width.checked_mul(height),
+            Some(vec.len()),
+            "board dimensions {}x{} do not match {} cells",
+            width,
+            height,
+            vec.len()
+        );
         let alloc: Box<[T]> = vec.into();
         let raw_ptr = Box::into_raw(alloc).cast::<T>();
         // SAFETY: Box::into_raw always returns a non-null pointer
@@ -36,11 +136,24 @@ impl<T> Board<T> {
         self.height
     }
     pub fn set_row(&mut self, i: usize, vec: Vec<T>) {
+        if let Err(e) = self.set_row_checked(i, vec) {
+            panic!("{}", e);
+        }
+    }
+    /// Like [`Board::set_row`], but reports a row of the wrong length instead of panicking. Still
+    /// panics if `i` is out of bounds.
+    pub fn set_row_checked(&mut self, i: usize, vec: Vec<T>) -> Result<(), LengthMismatch> {
         let slice = self.row_checked_mut(i).unwrap();
-        assert_eq!(slice.len(), vec.len());
+        if slice.len() != vec.len() {
+            return Err(LengthMismatch {
+                expected: slice.len(),
+                actual: vec.len(),
+            });
+        }
         for (s, v) in slice.iter_mut().zip(vec) {
             *s = v;
         }
+        Ok(())
     }
     pub fn set_col(&mut self, x: usize, vec: Vec<T>) {
         assert!(x < self.width);
@@ -66,18 +179,24 @@ impl<T> Board<T> {
         (i < self.height).then(|| unsafe { self.row_unchecked_mut(i) })
     }
     pub unsafe fn row_unchecked(&self, i: usize) -> &[T] {
+        debug_assert!(i < self.height, "row_unchecked out of bounds");
         let ptr = self.ptr.as_ptr();
         // SAFETY: `self.ptr` is valid by the invariants of the type. The caller must ensure `i < self.width`, meaning this pointer is in bounds. Vec and Box never allocate more than isize::MAX bytes, so this add will not overflow.
         let start = unsafe { ptr.add(self.width * i) };
         unsafe { slice::from_raw_parts(start, self.width) }
     }
     pub unsafe fn row_unchecked_mut(&mut self, i: usize) -> &mut [T] {
+        debug_assert!(i < self.height, "row_unchecked_mut out of bounds");
         let ptr = self.ptr.as_ptr();
         // SAFETY: `self.ptr` is valid by the invariants of the type. The caller must ensure `i < self.width`, meaning this pointer is in bounds. Vec and Box never allocate more than isize::MAX bytes, so this add will not overflow.
         let start = unsafe { ptr.add(self.width * i) };
         unsafe { slice::from_raw_parts_mut(start, self.width) }
     }
     pub unsafe fn pos_unchecked_mut(&mut self, x: usize, y: usize) -> &mut T {
+        debug_assert!(
+            x < self.width && y < self.height,
+            "pos_unchecked_mut out of bounds"
+        );
         let ptr = self.ptr.as_ptr();
         // SAFETY: `self.ptr` is valid by the invariants of the type. The caller must ensure `i < self.width`, meaning this pointer is in bounds. Vec and Box never allocate more than isize::MAX bytes, so this add will not overflow.
         unsafe { &mut *ptr.add(x + self.width * y) }
@@ -89,6 +208,10 @@ impl<T> Board<T> {
         self.pos_checked_mut(x, y).unwrap()
     }
     pub const unsafe fn pos_unchecked(&self, x: usize, y: usize) -> &T {
+        debug_assert!(
+            x < self.width && y < self.height,
+            "pos_unchecked out of bounds"
+        );
         // FIXME: cast to `*const T` required to make this function `const`.
         let ptr = self.ptr.as_ptr() as *const T;
         // SAFETY: `self.ptr` is valid by the invariants of the type. The caller must ensure `i < self.width`, meaning this pointer is in bounds. Vec and Box never allocate more than isize::MAX bytes, so this add will not overflow.
@@ -98,10 +221,116 @@ impl<T> Board<T> {
         let ptr = self.ptr.as_ptr();
         unsafe { slice::from_raw_parts(ptr, self.width * self.height) }
     }
+    /// Iterates over every cell in row-major order along with its `(x, y)` position.
+    pub fn iter_positions(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        // A zero-width board has no cells, so the division below is never reached.
+        let width = self.width;
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .map(move |(i, v)| (i % width, i / width, v))
+    }
     pub fn as_slice_mut(&mut self) -> &mut [T] {
         let ptr = self.ptr.as_ptr();
         unsafe { slice::from_raw_parts_mut(ptr, self.width * self.height) }
     }
+    /// Iterates over groups of `n` consecutive rows, each as one row-major slice. The last group
+    /// has fewer rows if `n` doesn't divide the height. A board with no cells yields nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn chunks_rows(&self, n: usize) -> slice::Chunks<'_, T> {
+        assert!(n > 0, "row chunks must contain at least one row");
+        self.as_slice().chunks(n.saturating_mul(self.width).max(1))
+    }
+    /// Whether a flood fill from the first `target` cell, moving only through `passable` cells,
+    /// reaches every `target` cell. Targets must be passable.
+    fn connected_through<F, G>(&self, adjacency: Adjacency, target: F, passable: G) -> bool
+    where
+        F: Fn(&T) -> bool,
+        G: Fn(&T) -> bool,
+    {
+        let cells = self.as_slice();
+        let Some(start) = cells.iter().position(&target) else {
+            return true;
+        };
+        let mut seen = vec![false; cells.len()];
+        seen[start] = true;
+        let mut stack = vec![(start % self.width, start / self.width)];
+        let mut reached = 1;
+        while let Some((x, y)) = stack.pop() {
+            for (nx, ny) in self.neighbors_with(x, y, adjacency) {
+                let i = nx + self.width * ny;
+                if passable(&cells[i]) && !seen[i] {
+                    seen[i] = true;
+                    reached += usize::from(target(&cells[i]));
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        reached == cells.iter().filter(|v| target(v)).count()
+    }
+    /// The positions orthogonally next to `(x, y)` that are on the board, in row-major order.
+    pub fn neighbors(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> {
+        self.neighbors_with(x, y, Adjacency::Orthogonal)
+    }
+    /// Like [`Board::neighbors`], but with a choice of which cells count as next to each other.
+    pub fn neighbors_with(
+        &self,
+        x: usize,
+        y: usize,
+        adjacency: Adjacency,
+    ) -> impl Iterator<Item = (usize, usize)> {
+        const ORTHOGONAL: &[(isize, isize)] = &[(0, -1), (-1, 0), (1, 0), (0, 1)];
+        const EIGHT_WAY: &[(isize, isize)] = &[
+            (-1, -1),
+            (0, -1),
+            (1, -1),
+            (-1, 0),
+            (1, 0),
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+        ];
+        let offsets = match adjacency {
+            Adjacency::Orthogonal => ORTHOGONAL,
+            Adjacency::EightWay => EIGHT_WAY,
+        };
+        let (width, height) = (self.width, self.height);
+        offsets.iter().filter_map(move |&(dx, dy)| {
+            let (nx, ny) = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+            (nx < width && ny < height).then_some((nx, ny))
+        })
+    }
+    /// Mirrors a square board along its main diagonal without allocating, so the cell at
+    /// `(x, y)` moves to `(y, x)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the board isn't square. Use [`Board::transpose`] for other boards.
+    pub fn transpose_in_place(&mut self) {
+        assert_eq!(
+            self.width, self.height,
+            "only square boards can be transposed in place, use `transpose` instead"
+        );
+        let n = self.width;
+        let cells = self.as_slice_mut();
+        for y in 0..n {
+            for x in 0..y {
+                cells.swap(x + n * y, y + n * x);
+            }
+        }
+    }
+    /// The cells in row-major order along with the board's width and height.
+    pub fn parts(&self) -> (&[T], usize, usize) {
+        (self.as_slice(), self.width, self.height)
+    }
+    /// The mutable analog of [`Board::parts`]. The dimensions can't be changed through it.
+    pub fn parts_mut(&mut self) -> (&mut [T], usize, usize) {
+        let (width, height) = (self.width, self.height);
+        (self.as_slice_mut(), width, height)
+    }
 }
 
 impl<T> Board<T>
@@ -113,6 +342,7 @@ where
         unsafe { Self::from_vec(vec, width, height) }
     }
     pub unsafe fn col_unchecked(&self, x: usize) -> Vec<T> {
+        debug_assert!(x < self.width, "col_unchecked out of bounds");
         (0..self.height)
             .map(|y| unsafe { self.pos_unchecked(x, y) }.clone())
             .collect()
@@ -124,6 +354,19 @@ where
     pub fn col(&self, x: usize) -> Vec<T> {
         self.col_checked(x).unwrap()
     }
+    /// Copies the cells out in column-major order: columns outer, rows inner.
+    pub fn as_col_major(&self) -> Vec<T> {
+        (0..self.width)
+            .flat_map(|x| (0..self.height).map(move |y| (x, y)))
+            // SAFETY: Both ranges are bounded by the board's dimensions.
+            .map(|(x, y)| unsafe { self.pos_unchecked(x, y) }.clone())
+            .collect()
+    }
+    /// A copy mirrored along the main diagonal: columns become rows, and a `w`x`h` board becomes
+    /// `h`x`w`.
+    pub fn transpose(&self) -> Self {
+        Self::new_from_fn(self.height, self.width, |x, y| self.row(x)[y].clone())
+    }
     pub fn set_row_slice(&mut self, i: usize, src: &[T]) {
         let slice = self.row_checked_mut(i).unwrap();
         assert_eq!(slice.len(), src.len());
@@ -133,6 +376,216 @@ where
     }
 }
 
+#[cfg(feature = "ndarray")]
+impl<T> Board<T>
+where
+    T: Clone,
+{
+    /// Copies the board into an array of shape `(height, width)`: rows are axis 0, so the cell at
+    /// `(x, y)` is `array[[y, x]]`.
+    pub fn to_array2(&self) -> ndarray::Array2<T> {
+        // The shape always matches the number of cells.
+        ndarray::Array2::from_shape_vec((self.height, self.width), self.as_slice().to_vec())
+            .unwrap()
+    }
+    /// Builds a board from an array laid out as in [`Board::to_array2`]. Views and arrays in any
+    /// memory order are accepted.
+    pub fn from_array2<S>(array: &ndarray::ArrayBase<S, ndarray::Ix2>) -> Self
+    where
+        S: ndarray::Data<Elem = T>,
+    {
+        let (height, width) = array.dim();
+        Self::new_from_fn(width, height, |x, y| array[[y, x]].clone())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync> Board<T> {
+    /// Iterates over every cell in parallel, in the same row-major order as [`Board::as_slice`].
+    pub fn par_iter(&self) -> rayon::slice::Iter<'_, T> {
+        use rayon::iter::IntoParallelRefIterator;
+        self.as_slice().par_iter()
+    }
+}
+
+impl Board<Cell> {
+    /// The inclusive `(min_x, min_y, max_x, max_y)` corners of the smallest rectangle containing
+    /// every filled cell, or `None` if no cell is filled.
+    pub fn bounding_box(&self) -> Option<(usize, usize, usize, usize)> {
+        self.iter_positions()
+            .filter(|&(_, _, &v)| v)
+            .fold(None, |bounds, (x, y, _)| match bounds {
+                None => Some((x, y, x, y)),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)))
+                }
+            })
+    }
+    /// Whether every filled cell can reach every other through orthogonally adjacent filled
+    /// cells. A board with no filled cells counts as connected.
+    pub fn filled_is_connected(&self) -> bool {
+        self.filled_is_connected_with(Adjacency::Orthogonal)
+    }
+    /// Like [`Board::filled_is_connected`], but with a choice of which cells count as adjacent.
+    pub fn filled_is_connected_with(&self, adjacency: Adjacency) -> bool {
+        self.connected_through(adjacency, |&v| v, |&v| v)
+    }
+    /// Shrinks the board by `factor` along both axes. Each cell of the result covers a
+    /// `factor`x`factor` block, and is filled if any cell in the block is. Blocks on the right and
+    /// bottom edges may be smaller.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is zero.
+    pub fn downsample(&self, factor: usize) -> Self {
+        assert!(factor > 0, "downsampling factor must be at least 1");
+        let (width, height) = (self.width.div_ceil(factor), self.height.div_ceil(factor));
+        Self::new_from_fn(width, height, |x, y| {
+            let mut rows = y * factor..((y + 1) * factor).min(self.height);
+            let cols = x * factor..((x + 1) * factor).min(self.width);
+            rows.any(|y| self.row(y)[cols.clone()].contains(&true))
+        })
+    }
+    /// The smallest whole downsampling factor that makes the board at most `max_width` cells
+    /// wide, and the board downsampled by it. A board that already fits is returned unchanged
+    /// with a factor of 1.
+    pub fn fit_width(&self, max_width: usize) -> (usize, Self) {
+        let factor = self.width.div_ceil(max_width.max(1)).max(1);
+        (factor, self.downsample(factor))
+    }
+    /// The most runs in any row and in any column, i.e. the longest row and column hints this
+    /// picture would have. Counts runs without building the hints.
+    pub fn max_runs(&self) -> (usize, usize) {
+        let row_runs = (0..self.height).map(|y| count_runs(self.row(y).iter().copied()));
+        let col_runs =
+            (0..self.width).map(|x| count_runs((0..self.height).map(|y| self.row(y)[x])));
+        (row_runs.max().unwrap_or(0), col_runs.max().unwrap_or(0))
+    }
+    /// Swaps filled and empty cells, giving the picture's negative.
+    pub fn invert(&mut self) {
+        for cell in self.as_slice_mut() {
+            *cell = !*cell;
+        }
+    }
+    /// Encodes the board as its width and height, each a little-endian `u64`, followed by one bit
+    /// per cell in row-major order, least significant bit first. Unused bits in the last byte are
+    /// zero.
+    pub fn to_packed_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(PACKED_HEADER_LEN + self.as_slice().len().div_ceil(8));
+        bytes.extend_from_slice(&(self.width as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.height as u64).to_le_bytes());
+        for chunk in self.as_slice().chunks(8) {
+            let byte = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (i, &filled)| byte | (u8::from(filled) << i));
+            bytes.push(byte);
+        }
+        bytes
+    }
+    /// Decodes a board written by [`Board::to_packed_bytes`]. Returns `None` if the length doesn't
+    /// match the dimensions in the header or an unused bit is set.
+    pub fn from_packed_bytes(bytes: &[u8]) -> Option<Self> {
+        let (header, cells) = bytes.split_at_checked(PACKED_HEADER_LEN)?;
+        let (width, height) = header.split_at(8);
+        let width = usize::try_from(u64::from_le_bytes(width.try_into().ok()?)).ok()?;
+        let height = usize::try_from(u64::from_le_bytes(height.try_into().ok()?)).ok()?;
+        let len = width.checked_mul(height)?;
+        if cells.len() != len.div_ceil(8) {
+            return None;
+        }
+        let padding = cells.len() * 8 - len;
+        if padding > 0 && cells[cells.len() - 1] >> (8 - padding) != 0 {
+            return None;
+        }
+        let cells = (0..len)
+            .map(|i| cells[i / 8] & (1 << (i % 8)) != 0)
+            .collect();
+        Some(Self::from_row_major(width, height, cells))
+    }
+    /// Writes the board as a `WxH` header line followed by one line per row, each ending in a
+    /// newline. Unlike the [`fmt::Display`] output, this keeps the dimensions of boards with no
+    /// columns.
+    pub fn to_text_with_header(&self) -> String {
+        use alloc::format;
+
+        let mut text = format!("{}x{}\n", self.width, self.height);
+        for y in 0..self.height {
+            text.extend(self.row(y).iter().map(|&v| display_bool(v)));
+            text.push('\n');
+        }
+        text
+    }
+    /// Reads a board written by [`Board::to_text_with_header`]. Returns `None` if the header is
+    /// malformed, the rows don't match its dimensions, or a cell is neither `X` nor `.`.
+    pub fn from_text_with_header(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        let (width, height) = lines.next()?.split_once('x')?;
+        let (width, height): (usize, usize) = (width.parse().ok()?, height.parse().ok()?);
+        // The header isn't trusted with an allocation: cells are only stored as rows turn up.
+        let mut cells = vec![];
+        for _ in 0..height {
+            let row = lines.next()?;
+            if row.chars().count() != width {
+                return None;
+            }
+            for c in row.chars() {
+                cells.push(match c {
+                    'X' => true,
+                    '.' => false,
+                    _ => return None,
+                });
+            }
+        }
+        if lines.next().is_some() || Some(cells.len()) != width.checked_mul(height) {
+            return None;
+        }
+        Some(Self::new_from_fn(width, height, |x, y| {
+            cells[y * width + x]
+        }))
+    }
+}
+
+/// The number of filled cells that start a run, i.e. aren't right after another filled cell.
+fn count_runs(line: impl Iterator<Item = Cell>) -> usize {
+    line.fold((0, false), |(runs, previous), filled| {
+        (runs + usize::from(filled && !previous), filled)
+    })
+    .0
+}
+
+/// The size of the width and height written before the cells by [`Board::to_packed_bytes`].
+const PACKED_HEADER_LEN: usize = 16;
+
+/// Marks every cell where the given solutions don't all agree. Returns an empty board if there
+/// are no solutions.
+///
+/// # Panics
+///
+/// Panics if the solutions have different dimensions.
+pub fn solution_differences(solutions: &[Board<Cell>]) -> Board<bool> {
+    let Some((first, rest)) = solutions.split_first() else {
+        return Board::default();
+    };
+    for other in rest {
+        assert_eq!(
+            (other.width, other.height),
+            (first.width, first.height),
+            "solutions have different dimensions"
+        );
+    }
+    let mut differences = Board::new(first.width, first.height, false);
+    for (i, (diff, &value)) in differences
+        .as_slice_mut()
+        .iter_mut()
+        .zip(first.as_slice())
+        .enumerate()
+    {
+        *diff = rest.iter().any(|other| other.as_slice()[i] != value);
+    }
+    differences
+}
+
 impl<T> Clone for Board<T>
 where
     T: Clone,
@@ -141,6 +594,14 @@ where
         let vec = self.as_slice().to_vec();
         unsafe { Self::from_vec(vec, self.width, self.height) }
     }
+    /// Reuses the existing allocation when `source` has the same dimensions.
+    fn clone_from(&mut self, source: &Self) {
+        if (self.width, self.height) == (source.width, source.height) {
+            self.as_slice_mut().clone_from_slice(source.as_slice());
+        } else {
+            *self = source.clone();
+        }
+    }
 }
 
 impl<T> fmt::Debug for Board<T>
@@ -156,6 +617,47 @@ where
     }
 }
 
+impl<T> Board<T>
+where
+    T: PartialEq,
+{
+    /// Whether row `y` exists and equals `other`.
+    pub fn row_eq(&self, y: usize, other: &[T]) -> bool {
+        self.row_checked(y) == Some(other)
+    }
+    /// Whether column `x` exists and equals `other`, compared in place without copying it out.
+    pub fn col_eq(&self, x: usize, other: &[T]) -> bool {
+        x < self.width
+            && other.len() == self.height
+            // SAFETY: `x < self.width` was just checked and `y` ranges over the height.
+            && other.iter().enumerate().all(|(y, v)| unsafe { self.pos_unchecked(x, y) } == v)
+    }
+}
+
+impl<T> PartialEq for Board<T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T> Eq for Board<T> where T: Eq {}
+
+impl<T> Hash for Board<T>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.as_slice().hash(state);
+    }
+}
+
 impl<T> Default for Board<T> {
     fn default() -> Self {
         Self {
@@ -166,6 +668,12 @@ impl<T> Default for Board<T> {
     }
 }
 
+// SAFETY: A board uniquely owns its cells just like the `Box<[T]>` it was built from, so it can
+// cross threads whenever the cells can.
+unsafe impl<T: Send> Send for Board<T> {}
+// SAFETY: Shared access to a board only ever hands out shared references to its cells.
+unsafe impl<T: Sync> Sync for Board<T> {}
+
 impl<T> Drop for Board<T> {
     fn drop(&mut self) {
         let fat_ptr = self.as_slice_mut() as *mut [T];
@@ -173,7 +681,8 @@ impl<T> Drop for Board<T> {
     }
 }
 
-impl fmt::Display for Board<bool> {
+// Written in terms of `Cell` so solved boards stay printable if its definition changes.
+impl fmt::Display for Board<Cell> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for y in 0..self.height {
             if y > 0 {
@@ -188,7 +697,7 @@ impl fmt::Display for Board<bool> {
     }
 }
 
-impl fmt::Display for Board<Option<bool>> {
+impl fmt::Display for Board<Option<Cell>> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for y in 0..self.height {
             if y > 0 {
@@ -203,7 +712,36 @@ impl fmt::Display for Board<Option<bool>> {
     }
 }
 
-const fn display_bool(x: bool) -> char {
+impl Board<Option<Cell>> {
+    /// Whether the filled cells could still end up orthogonally connected, i.e. every filled cell
+    /// can reach every other through cells that are filled or unknown.
+    pub fn filled_could_connect(&self) -> bool {
+        self.connected_through(
+            Adjacency::Orthogonal,
+            |&v| v == Some(true),
+            |&v| v != Some(false),
+        )
+    }
+    /// Like the [`fmt::Display`] output, but draws the filled cells in `guessed` as `x`, to tell
+    /// speculative cells apart from ones line logic determined.
+    pub fn render_with_guesses(&self, guessed: &[(usize, usize)]) -> String {
+        let mut out = String::new();
+        for y in 0..self.height {
+            if y > 0 {
+                out.push('\n');
+            }
+            for (x, &v) in self.row(y).iter().enumerate() {
+                out.push(match v {
+                    Some(true) if guessed.contains(&(x, y)) => 'x',
+                    v => display_option_bool(v),
+                });
+            }
+        }
+        out
+    }
+}
+
+const fn display_bool(x: Cell) -> char {
     if x {
         'X'
     } else {
@@ -211,19 +749,684 @@ const fn display_bool(x: bool) -> char {
     }
 }
 
-const fn display_option_bool(x: Option<bool>) -> char {
+const fn display_option_bool(x: Option<Cell>) -> char {
     match x {
         Some(y) => display_bool(y),
         None => '?',
     }
 }
 
+impl<T> Board<T>
+where
+    Self: fmt::Display,
+{
+    /// Renders the board with every row prefixed by its zero-padded index and a header giving
+    /// each column's index, read top to bottom.
+    pub fn render_with_coords(&self) -> String {
+        use alloc::format;
+        use alloc::string::ToString;
+
+        let digits = |len: usize| len.saturating_sub(1).to_string().len();
+        let (row_digits, col_digits) = (digits(self.height), digits(self.width));
+        let mut out = String::new();
+        if self.width > 0 {
+            for place in (0..col_digits as u32).rev() {
+                out.extend(core::iter::repeat_n(' ', row_digits + 1));
+                out.extend((0..self.width).map(|x| {
+                    let digit = x / 10usize.pow(place) % 10;
+                    char::from_digit(digit as u32, 10).unwrap()
+                }));
+                out.push('\n');
+            }
+        }
+        let text = self.to_string();
+        for (y, line) in text.lines().enumerate().take(self.height) {
+            out += &format!("{:0w$} {}", y, line, w = row_digits);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// How a partly filled in board compares to a solution, cell by cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AttemptScore {
+    /// Filled in both.
+    pub correct_filled: usize,
+    /// Empty in both.
+    pub correct_empty: usize,
+    /// Marked in the attempt, but the other way in the solution.
+    pub wrong: usize,
+    /// Not yet marked in the attempt.
+    pub unknown: usize,
+}
+
+impl AttemptScore {
+    /// Whether every cell is marked and none is wrong.
+    pub const fn is_solved(&self) -> bool {
+        self.wrong == 0 && self.unknown == 0
+    }
+}
+
+/// Grades a player's `attempt` against `solution`. Fails if the boards aren't the same size.
+pub fn score_attempt(
+    attempt: &Board<Option<Cell>>,
+    solution: &Board<Cell>,
+) -> Result<AttemptScore, DimensionMismatch> {
+    if (attempt.width, attempt.height) != (solution.width, solution.height) {
+        return Err(DimensionMismatch {
+            expected: (solution.width, solution.height),
+            actual: (attempt.width, attempt.height),
+        });
+    }
+    let mut score = AttemptScore::default();
+    for (&marked, &answer) in attempt.as_slice().iter().zip(solution.as_slice()) {
+        let count = match marked {
+            None => &mut score.unknown,
+            Some(v) if v != answer => &mut score.wrong,
+            Some(true) => &mut score.correct_filled,
+            Some(false) => &mut score.correct_empty,
+        };
+        *count += 1;
+    }
+    Ok(score)
+}
+
+/// Renders two boards side by side, followed by a grid marking the cells where they differ with
+/// `#`. Boards with different dimensions are shown without the difference grid.
+pub fn render_diff<T>(left: &Board<T>, right: &Board<T>) -> String
+where
+    T: PartialEq,
+    Board<T>: fmt::Display,
+{
+    use alloc::format;
+    use alloc::string::ToString;
+
+    let (left_text, right_text) = (left.to_string(), right.to_string());
+    let mut left_lines = left_text.lines();
+    let mut right_lines = right_text.lines();
+    let same_dims = left.width == right.width && left.height == right.height;
+    let mut out = String::new();
+    for y in 0..left.height.max(right.height) {
+        let l = left_lines.next().unwrap_or("");
+        let r = right_lines.next().unwrap_or("");
+        let mut line = format!("{:<w$}  {:<w2$}", l, r, w = left.width, w2 = right.width);
+        if same_dims {
+            line += "  ";
+            line.extend(
+                left.row(y)
+                    .iter()
+                    .zip(right.row(y))
+                    .map(|(a, b)| if a == b { '.' } else { '#' }),
+            );
+        }
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+/// Tiles the solutions side by side, `cols` to a row, each under a label numbering it from 1.
+/// Solutions in the same row are top-aligned and rows of tiles are separated by a blank line.
+/// Returns an empty string if there are no solutions. A `cols` of zero is treated as one.
+pub fn solutions_to_contact_sheet(solutions: &[Board<Cell>], cols: usize) -> String {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    let cols = cols.max(1);
+    let mut out = String::new();
+    for (chunk_index, chunk) in solutions.chunks(cols).enumerate() {
+        if chunk_index > 0 {
+            out.push('\n');
+        }
+        let labels: Vec<String> = (0..chunk.len())
+            .map(|i| format!("#{}", chunk_index * cols + i + 1))
+            .collect();
+        let texts: Vec<String> = chunk.iter().map(ToString::to_string).collect();
+        let widths: Vec<usize> = chunk
+            .iter()
+            .zip(&labels)
+            .map(|(board, label)| board.width.max(label.len()))
+            .collect();
+        let height = chunk.iter().map(|board| board.height).max().unwrap_or(0);
+        let mut push_line = |cells: &mut dyn Iterator<Item = &str>| {
+            let line = cells
+                .zip(&widths)
+                .map(|(cell, &w)| format!("{:<w$}", cell, w = w))
+                .collect::<Vec<_>>()
+                .join("  ");
+            out += line.trim_end();
+            out.push('\n');
+        };
+        push_line(&mut labels.iter().map(String::as_str));
+        for y in 0..height {
+            push_line(&mut texts.iter().map(|text| text.lines().nth(y).unwrap_or("")));
+        }
+    }
+    out
+}
+
+/// Whether `b` is some rotation or reflection of `a`, including `a` itself.
+pub fn equivalent_under_symmetry<T: PartialEq>(a: &Board<T>, b: &Board<T>) -> bool {
+    for transpose in [false, true] {
+        let (width, height) = if transpose {
+            (b.height, b.width)
+        } else {
+            (b.width, b.height)
+        };
+        if (a.width, a.height) != (width, height) {
+            continue;
+        }
+        for mirror in [false, true] {
+            for flip in [false, true] {
+                let matches = a.iter_positions().all(|(x, y, cell)| {
+                    let x = if mirror { width - 1 - x } else { x };
+                    let y = if flip { height - 1 - y } else { y };
+                    let (x, y) = if transpose { (y, x) } else { (x, y) };
+                    b.row(y)[x] == *cell
+                });
+                if matches {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Like `assert_eq!`, but prints mismatched boards as grids with the differing cells marked.
+#[cfg(test)]
+macro_rules! assert_board_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if left != right {
+                    panic!(
+                        "assertion `left == right` failed ({}x{} vs {}x{})\n{}",
+                        left.width(),
+                        left.height(),
+                        right.width(),
+                        right.height(),
+                        $crate::board::render_diff(left, right)
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) use assert_board_eq;
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::Cell as DropCell;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
 
     #[test]
     fn sound_dangling_drop() {
         let _drop: Board<bool> = Board::default();
     }
+
+    struct DropCounter {
+        drops: Rc<DropCell<usize>>,
+        panics: bool,
+    }
+
+    impl DropCounter {
+        fn new(drops: &Rc<DropCell<usize>>, panics: bool) -> Self {
+            let drops = Rc::clone(drops);
+            Self { drops, panics }
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+            if self.panics {
+                panic!("drop panic");
+            }
+        }
+    }
+
+    #[test]
+    fn set_row_drop_panic() {
+        let drops = Rc::new(DropCell::new(0));
+        let mut b = Board::new_with(2, 1, || DropCounter::new(&drops, false));
+        b.row_mut(0)[0].panics = true;
+        let new_row = vec![
+            DropCounter::new(&drops, false),
+            DropCounter::new(&drops, false),
+        ];
+        let result = panic::catch_unwind(AssertUnwindSafe(|| b.set_row(0, new_row)));
+        assert!(result.is_err());
+        // The panicking cell was replaced, and the unused replacement was dropped.
+        assert_eq!(drops.get(), 2);
+        drop(b);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn drop_panic() {
+        let drops = Rc::new(DropCell::new(0));
+        let mut b = Board::new_with(3, 3, || DropCounter::new(&drops, false));
+        b.as_slice_mut()[4].panics = true;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| drop(b)));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn overflowing_dimensions() {
+        let _b: Board<()> = Board::new_with(usize::MAX / 2 + 1, 2, || ());
+    }
+
+    #[test]
+    fn diff_rendering() {
+        let a = Board::new_from_fn(3, 2, |x, _| x == 0);
+        let b = Board::new_from_fn(3, 2, |x, y| x == 0 || y == 1);
+        assert_board_eq!(a, a.clone());
+        assert_ne!(a, b);
+        assert_eq!(render_diff(&a, &b), "X..  X..  ...\nX..  XXX  .##\n");
+    }
+
+    #[test]
+    fn clone_from_reuses_allocation() {
+        let source = Board::new_from_fn(3, 2, |x, y| x + y);
+        let mut same = Board::new(3, 2, 0);
+        let ptr = same.as_slice().as_ptr();
+        same.clone_from(&source);
+        assert_eq!(same, source);
+        assert_eq!(same.as_slice().as_ptr(), ptr);
+
+        let mut different = Board::new(2, 3, 0);
+        different.clone_from(&source);
+        assert_eq!(different, source);
+        assert_eq!((different.width(), different.height()), (3, 2));
+    }
+
+    #[test]
+    fn line_eq() {
+        let b = Board::new_from_fn(3, 2, |x, y| x + 3 * y);
+        assert!(b.row_eq(1, &[3, 4, 5]));
+        assert!(!b.row_eq(1, &[3, 4]));
+        assert!(!b.row_eq(2, &[]));
+        assert!(b.col_eq(2, &[2, 5]));
+        assert!(!b.col_eq(2, &[2, 5, 8]));
+        assert!(!b.col_eq(0, &[0, 4]));
+        assert!(!b.col_eq(3, &[]));
+    }
+
+    #[test]
+    fn differences() {
+        let a = Board::new_from_fn(3, 1, |x, _| x == 0);
+        let b = Board::new_from_fn(3, 1, |x, _| x == 1);
+        let c = Board::new_from_fn(3, 1, |x, _| x < 2);
+        assert_eq!(
+            solution_differences(std::slice::from_ref(&a)).to_string(),
+            "..."
+        );
+        assert_eq!(solution_differences(&[a, b, c]).to_string(), "XX.");
+        assert_eq!(solution_differences(&[]).width(), 0);
+    }
+
+    #[test]
+    fn packed_bytes() {
+        let b = Board::new_from_fn(3, 3, |x, y| x == y || x == 2);
+        let bytes = b.to_packed_bytes();
+        assert_eq!(bytes.len(), 16 + 2);
+        assert_eq!(
+            bytes[..16],
+            [3, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(bytes[16..], [0b0011_0101, 0b1]);
+        assert_eq!(Board::from_packed_bytes(&bytes), Some(b));
+
+        let empty: Board<Cell> = Board::new_default(0, 4);
+        assert_eq!(
+            Board::from_packed_bytes(&empty.to_packed_bytes()),
+            Some(empty)
+        );
+
+        let mut padded = bytes.clone();
+        padded[17] |= 0b10;
+        assert_eq!(Board::from_packed_bytes(&padded), None);
+        assert_eq!(Board::from_packed_bytes(&bytes[..17]), None);
+        assert_eq!(Board::from_packed_bytes(&bytes[..10]), None);
+        // No columns but a vast number of rows still decodes at once.
+        let mut tall = [0; 16];
+        tall[8..].copy_from_slice(&(1u64 << 62).to_le_bytes());
+        let tall = Board::from_packed_bytes(&tall).unwrap();
+        assert_eq!((tall.width(), tall.height()), (0, 1 << 62));
+        let mut huge = bytes;
+        huge[..16].fill(0xff);
+        assert_eq!(Board::from_packed_bytes(&huge), None);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_interop() {
+        let b = Board::new_from_fn(3, 2, |x, y| x + 10 * y);
+        let array = b.to_array2();
+        assert_eq!(array.dim(), (2, 3));
+        assert_eq!(array[[1, 2]], 12);
+        assert_eq!(array.row(1).to_vec(), b.row(1));
+        assert_eq!(Board::from_array2(&array), b);
+        let transposed = Board::from_array2(&array.t());
+        assert_eq!((transposed.width(), transposed.height()), (2, 3));
+        assert_eq!(transposed.row(2), [2, 12]);
+    }
+
+    #[test]
+    fn coordinates() {
+        let b = Board::new_from_fn(12, 2, |x, y| x == 11 || y == 1 && x == 0);
+        assert_eq!(
+            b.render_with_coords(),
+            "  000000000011\n  012345678901\n0 ...........X\n1 X..........X\n"
+        );
+        let tall = Board::new(1, 11, false);
+        let text = tall.render_with_coords();
+        assert!(text.starts_with("   0\n00 .\n"));
+        assert!(text.ends_with("10 .\n"));
+    }
+
+    #[test]
+    fn memory_layout() {
+        use core::mem::size_of;
+        assert_eq!(size_of::<Option<Cell>>(), 1);
+        assert_eq!(size_of::<Board<Option<Cell>>>(), 3 * size_of::<usize>());
+        assert_eq!(size_of::<Option<Board<Cell>>>(), size_of::<Board<Cell>>());
+    }
+
+    // In bounds, `x + width * y` is below the cell count, which `from_vec` checked fits in a
+    // `usize`, so the offset can't overflow. Debug builds also catch an overflow if it ever did.
+    #[test]
+    fn far_corner() {
+        let mut b = Board::new(1 << 12, 1 << 8, 0u8);
+        let (x, y) = (b.width() - 1, b.height() - 1);
+        *b.pos_mut(x, y) = 7;
+        assert_eq!(b.as_slice().last(), Some(&7));
+        assert_eq!(b.row(y)[x], 7);
+        assert_eq!(b.col(x)[y], 7);
+        assert_eq!(b.pos_checked_mut(x + 1, y), None);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "pos_unchecked out of bounds")]
+    fn unchecked_out_of_bounds() {
+        let b = Board::new(2, 2, 0);
+        // The assertion fires before any out-of-bounds pointer is formed.
+        let _ = unsafe { b.pos_unchecked(2, 0) };
+    }
+
+    #[test]
+    fn row_length_mismatch() {
+        let mut b = Board::new(3, 2, 0);
+        assert_eq!(
+            b.set_row_checked(1, vec![1, 2]),
+            Err(LengthMismatch {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(b.row(1), [0, 0, 0]);
+        assert_eq!(b.set_row_checked(1, vec![1, 2, 3]), Ok(()));
+        assert_eq!(b.row(1), [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "line has 4 cells but the board expects 3")]
+    fn set_row_wrong_length() {
+        Board::new(3, 2, 0).set_row(0, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn row_chunks() {
+        let b = Board::new_from_fn(2, 5, |x, y| x + 2 * y);
+        let chunks: Vec<_> = b.chunks_rows(2).collect();
+        assert_eq!(chunks, [&[0, 1, 2, 3][..], &[4, 5, 6, 7], &[8, 9]]);
+        assert_eq!(b.chunks_rows(usize::MAX).count(), 1);
+        let empty: Board<u8> = Board::new_default(0, 3);
+        assert_eq!(empty.chunks_rows(1).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one row")]
+    fn zero_row_chunks() {
+        let _ = Board::new(2, 2, 0).chunks_rows(0);
+    }
+
+    #[test]
+    fn bounds() {
+        let b = Board::new_from_fn(5, 4, |x, y| (x == 1 && y == 2) || (x == 3 && y == 1));
+        assert_eq!(b.bounding_box(), Some((1, 1, 3, 2)));
+        assert_eq!(Board::new(3, 3, false).bounding_box(), None);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_cells() {
+        use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+        let b = Board::new_from_fn(300, 200, |x, y| (x + y) % 3 == 0);
+        let filled = b.par_iter().filter(|&&v| v).count();
+        assert_eq!(filled, b.as_slice().iter().filter(|&&v| v).count());
+        let indices: Vec<usize> = b.par_iter().enumerate().map(|(i, _)| i).collect();
+        assert!(indices.iter().copied().eq(0..300 * 200));
+    }
+
+    #[test]
+    fn text_with_header() {
+        let b = Board::new_from_fn(3, 2, |x, y| x == y);
+        let text = b.to_text_with_header();
+        assert_eq!(text, "3x2\nX..\n.X.\n");
+        assert_eq!(Board::from_text_with_header(&text), Some(b));
+        let no_cols = Board::new(0, 3, false);
+        assert_eq!(no_cols.to_text_with_header(), "0x3\n\n\n\n");
+        assert_eq!(
+            Board::from_text_with_header(&no_cols.to_text_with_header()),
+            Some(no_cols)
+        );
+        assert_eq!(Board::from_text_with_header("2x1\nX.\nX.\n"), None);
+        assert_eq!(Board::from_text_with_header("2x2\nX.\n"), None);
+        assert_eq!(Board::from_text_with_header("2x1\nX?\n"), None);
+        assert_eq!(Board::from_text_with_header("X.\n"), None);
+        assert_eq!(
+            Board::from_text_with_header("4000000000x4000000000\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn contact_sheet() {
+        let boards = [
+            Board::new_from_fn(2, 2, |x, y| x == y),
+            Board::new_from_fn(2, 2, |x, y| x != y),
+            Board::new(3, 1, true),
+        ];
+        assert_eq!(
+            solutions_to_contact_sheet(&boards, 2),
+            "#1  #2\nX.  .X\n.X  X.\n\n#3\nXXX\n"
+        );
+        assert_eq!(
+            solutions_to_contact_sheet(&boards[1..], 5),
+            "#1  #2\n.X  XXX\nX.\n"
+        );
+        assert_eq!(solutions_to_contact_sheet(&[], 3), "");
+    }
+
+    #[test]
+    fn transposing() {
+        let mut square = Board::new_from_fn(3, 3, |x, y| x + 3 * y);
+        let copy = square.transpose();
+        square.transpose_in_place();
+        assert_eq!(square, copy);
+        assert_eq!(square.row(0), [0, 3, 6]);
+        assert_eq!(square.transpose().row(0), [0, 1, 2]);
+        let wide = Board::new_from_fn(3, 2, |x, y| x + 3 * y);
+        let tall = wide.transpose();
+        assert_eq!((tall.width(), tall.height()), (2, 3));
+        assert_eq!(tall.as_slice(), wide.as_col_major());
+        let mut empty: Board<u8> = Board::new_default(0, 0);
+        empty.transpose_in_place();
+    }
+
+    #[test]
+    #[should_panic(expected = "use `transpose` instead")]
+    fn transpose_non_square_in_place() {
+        Board::new(3, 2, 0).transpose_in_place();
+    }
+
+    #[test]
+    fn neighbors() {
+        let b = Board::new(3, 2, 0);
+        assert_eq!(b.neighbors(0, 0).collect::<Vec<_>>(), [(1, 0), (0, 1)]);
+        assert_eq!(
+            b.neighbors(1, 1).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (2, 1)]
+        );
+        let eight_way: Vec<_> = b.neighbors_with(2, 0, Adjacency::EightWay).collect();
+        assert_eq!(eight_way, [(1, 0), (1, 1), (2, 1)]);
+        assert_eq!(Board::new(1, 1, 0).neighbors(0, 0).count(), 0);
+    }
+
+    #[test]
+    fn connectivity() {
+        let picture = |s: &str, width| {
+            let cells: Vec<bool> = s.chars().map(|c| c == 'X').collect();
+            Board::new_from_fn(width, cells.len() / width, |x, y| cells[x + width * y])
+        };
+        assert!(picture("XX..XX", 3).filled_is_connected());
+        assert!(!picture("X.X", 3).filled_is_connected());
+        let diagonal = picture("X..X", 2);
+        assert!(!diagonal.filled_is_connected());
+        assert!(diagonal.filled_is_connected_with(Adjacency::EightWay));
+        assert!(Board::new(2, 2, false).filled_is_connected());
+        assert!(Board::<bool>::new_default(0, 0).filled_is_connected());
+    }
+
+    #[test]
+    fn possible_connections() {
+        let b = Board::new_from_fn(3, 2, |x, y| match (x, y) {
+            (0, 0) | (2, 0) => Some(true),
+            (1, 0) => None,
+            _ => Some(false),
+        });
+        assert!(b.filled_could_connect());
+        let mut split = b.clone();
+        *split.pos_mut(1, 0) = Some(false);
+        assert!(!split.filled_could_connect());
+        assert!(Board::new(2, 2, None).filled_could_connect());
+    }
+
+    #[test]
+    fn downsampling() {
+        let b = Board::new_from_fn(5, 3, |x, y| x == 4 || (x, y) == (1, 1));
+        assert_eq!(b.downsample(2).to_string(), "X.X\n..X");
+        assert_eq!(b.downsample(1), b);
+        assert_eq!(b.downsample(9).to_string(), "X");
+        let (factor, fitted) = b.fit_width(2);
+        assert_eq!((factor, fitted.width()), (3, 2));
+        assert_eq!(b.fit_width(80), (1, b.clone()));
+        assert_eq!(Board::new(0, 0, false).fit_width(0).0, 1);
+    }
+
+    #[test]
+    fn attempt_scoring() {
+        let solution = Board::new_from_fn(3, 2, |x, y| x == y);
+        let attempt = Board::new_from_fn(3, 2, |x, y| match (x, y) {
+            (0, 0) => Some(true),
+            (1, 0) | (2, 1) => Some(false),
+            (2, 0) => Some(true),
+            _ => None,
+        });
+        let score = score_attempt(&attempt, &solution).unwrap();
+        assert_eq!(
+            score,
+            AttemptScore {
+                correct_filled: 1,
+                correct_empty: 2,
+                wrong: 1,
+                unknown: 2,
+            }
+        );
+        assert!(!score.is_solved());
+        let perfect = Board::new_from_fn(3, 2, |x, y| Some(x == y));
+        assert!(score_attempt(&perfect, &solution).unwrap().is_solved());
+        assert_eq!(
+            score_attempt(&Board::new(2, 3, None), &solution),
+            Err(DimensionMismatch {
+                expected: (3, 2),
+                actual: (2, 3)
+            })
+        );
+    }
+
+    #[test]
+    fn run_counts() {
+        let b = Board::new_from_fn(5, 3, |x, y| (x + y) % 2 == 0 || y == 2);
+        assert_eq!(b.max_runs(), (3, 2));
+        assert_eq!(Board::new(4, 2, false).max_runs(), (0, 0));
+        assert_eq!(Board::new(0, 0, false).max_runs(), (0, 0));
+        let puzzle = crate::puzzle::hints_from_board(&b);
+        let longest = |lines: &[Vec<_>]| lines.iter().map(Vec::len).max().unwrap_or(0);
+        assert_eq!(b.max_runs(), (longest(&puzzle.rows), longest(&puzzle.cols)));
+    }
+
+    #[test]
+    fn invert() {
+        let original = Board::new_from_fn(3, 2, |x, y| x == y);
+        let mut b = original.clone();
+        b.invert();
+        assert_eq!(b.to_string(), ".XX\nX.X");
+        b.invert();
+        assert_eq!(b, original);
+    }
+
+    #[test]
+    fn symmetry() {
+        let l = Board::new_from_fn(3, 2, |x, y| y == 1 || x == 0);
+        let rotated = Board::new_from_fn(2, 3, |x, y| x == 0 || y == 2);
+        assert!(equivalent_under_symmetry(&l, &rotated));
+        assert!(equivalent_under_symmetry(&l, &l));
+        assert!(!equivalent_under_symmetry(&l, &Board::new(3, 2, true)));
+        assert!(!equivalent_under_symmetry(&l, &Board::new(3, 3, true)));
+    }
+
+    #[test]
+    fn positions() {
+        let b = Board::new_from_fn(2, 2, |x, y| (x, y));
+        assert!(b.iter_positions().all(|(x, y, &v)| v == (x, y)));
+        assert_eq!(b.iter_positions().count(), 4);
+        let empty: Board<bool> = Board::new_default(0, 5);
+        assert_eq!(empty.iter_positions().count(), 0);
+    }
+
+    #[test]
+    fn parts() {
+        let mut b = Board::new_from_fn(3, 2, |x, y| x + 3 * y);
+        let (cells, width, height) = b.parts();
+        assert_eq!((cells, width, height), (&[0, 1, 2, 3, 4, 5][..], 3, 2));
+        let (cells, width, _) = b.parts_mut();
+        cells[width] = 9;
+        assert_eq!(b.row(1), [9, 4, 5]);
+    }
+
+    #[test]
+    fn from_fn() {
+        let b = Board::new_from_fn(3, 2, |x, y| (x + y) % 2 == 0);
+        assert_eq!(b.to_string(), "X.X\n.X.");
+    }
+
+    #[test]
+    fn col_major() {
+        let mut b = Board::new(3, 2, 0);
+        b.set_row(0, vec![1, 2, 3]);
+        b.set_row(1, vec![4, 5, 6]);
+        assert_eq!(b.as_col_major(), [1, 4, 2, 5, 3, 6]);
+        assert_eq!(b.as_slice(), [1, 2, 3, 4, 5, 6]);
+    }
 }
@@ -1 +1,18 @@
 pub type Cell = bool;
+
+/// What the line solver needs to know about a cell, so the same machinery can work with
+/// monochrome and colored puzzles.
+pub trait CellLike: Copy + Eq {
+    /// Whether the cell is part of a run, as opposed to background.
+    fn is_filled(self) -> bool;
+    /// Whether an arrangement with this cell is allowed where the board already has `known`.
+    fn compatible(self, known: Self) -> bool {
+        self == known
+    }
+}
+
+impl CellLike for Cell {
+    fn is_filled(self) -> bool {
+        self
+    }
+}
@@ -0,0 +1,60 @@
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    Row,
+    Col,
+}
+
+/// A single step taken by the solver, recorded when logging is enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SolveEvent {
+    /// `removed` permutations of row `y` were ruled out by the current board.
+    FilteredRow { y: usize, removed: usize },
+    /// `removed` permutations of column `x` were ruled out by the current board.
+    FilteredCol { x: usize, removed: usize },
+    /// `cells` new cells were determined in the given line.
+    Deduced {
+        axis: Axis,
+        index: usize,
+        cells: usize,
+    },
+    /// The solver guessed that the cell at `(x, y)` is filled, saving the alternative.
+    Bifurcated { cell: (usize, usize) },
+    /// The solver restored the most recent saved alternative.
+    Backtracked,
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Row => write!(f, "row"),
+            Self::Col => write!(f, "column"),
+        }
+    }
+}
+
+impl fmt::Display for SolveEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::FilteredRow { y, removed } => {
+                write!(f, "row {}: ruled out {} arrangements", y, removed)
+            }
+            Self::FilteredCol { x, removed } => {
+                write!(f, "column {}: ruled out {} arrangements", x, removed)
+            }
+            Self::Deduced { axis, index, cells } => {
+                write!(f, "{} {}: deduced {} cells", axis, index, cells)
+            }
+            Self::Bifurcated { cell: (x, y) } => write!(f, "guessing cell ({}, {}) = filled", x, y),
+            Self::Backtracked => write!(f, "backtracking"),
+        }
+    }
+}
+
+pub fn record(log: &mut Option<Vec<SolveEvent>>, event: SolveEvent) {
+    if let Some(log) = log {
+        log.push(event);
+    }
+}
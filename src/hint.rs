@@ -1,6 +1,24 @@
-use crate::cell::Cell;
+use crate::cell::{Cell, CellLike};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hash::Hash;
+use core::num::NonZeroUsize;
+#[cfg(feature = "std")]
 use indexmap::IndexSet;
-use std::num::NonZeroUsize;
+
+/// How far apart consecutive runs in a line must be.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GapRule {
+    /// The minimum number of empty cells between two runs.
+    pub min_gap: usize,
+}
+
+impl Default for GapRule {
+    /// Standard nonograms separate runs by at least one empty cell.
+    fn default() -> Self {
+        Self { min_gap: 1 }
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Hint<'a> {
@@ -18,7 +36,41 @@ impl Hint<'_> {
         self.lines.len()
     }
 
+    pub const fn lines(&self) -> &[NonZeroUsize] {
+        self.lines
+    }
+
+    /// The length of each run, in order.
+    pub fn runs(&self) -> impl DoubleEndedIterator<Item = usize> + ExactSizeIterator + '_ {
+        self.lines.iter().map(|n| n.get())
+    }
+
+    /// The number of filled cells this hint describes. Saturates at `usize::MAX`.
+    pub fn total(&self) -> usize {
+        self.runs().fold(0, usize::saturating_add)
+    }
+
+    /// The shortest section this hint fits in: every run plus a single gap between each.
+    /// Saturates at `usize::MAX`, so a hint too long to count fits in no section.
+    pub fn min_length(&self) -> usize {
+        self.total().saturating_add(self.len().saturating_sub(1))
+    }
+
+    /// How many arrangements [`Hint::permutations`] produces for a section of `length` cells,
+    /// without generating them. Saturates at `usize::MAX`.
+    pub fn count_permutations(&self, length: usize) -> usize {
+        length
+            .checked_sub(self.min_length())
+            .map_or(0, |slack| count_placements(slack, self.len()))
+    }
+
     pub fn permutations(&self, length: usize) -> Vec<Vec<Cell>> {
+        self.permutations_with_gap(length, GapRule::default())
+    }
+
+    /// Like [`Hint::permutations`], but separates runs according to `gap` instead of by a
+    /// single empty cell.
+    pub fn permutations_with_gap(&self, length: usize, gap: GapRule) -> Vec<Vec<Cell>> {
         match self.lines.split_last() {
             None => vec![vec![false; length]], // no hints, which means only one solution: a blank row/column
             Some((line, &[])) => {
@@ -37,39 +89,52 @@ impl Hint<'_> {
                 }
                 perms
             }
-            Some((last, rest)) => {
-                let line_len = last.get();
-                if line_len > length {
-                    return vec![];
-                }
-                let last_array = [*last];
-                let line = Hint {
-                    // can't use Self since the slice has a shorter lifetime
-                    lines: last_array.as_slice(),
-                };
-                let rest = Self { lines: rest };
-                let mut perms = vec![];
-                for i in 1..length - line_len {
-                    let mut subperms = rest.permutations(i);
-                    let lastperms = line.permutations(length - i - 1);
-                    for p in &mut subperms {
-                        p.push(false); // add padding between lines
-                        for mut lp in lastperms.iter().cloned() {
-                            let mut perm = p.clone();
-                            perm.append(&mut lp);
-                            assert_eq!(perm.len(), length);
-                            perms.push(perm);
-                        }
+            Some(_) => self
+                .placements(length, gap)
+                .into_iter()
+                .map(|starts| {
+                    let mut v = vec![false; length];
+                    for (&start, line) in starts.iter().zip(self.lines) {
+                        v[start..start + line.get()].fill(true);
                     }
-                }
-                // awful, order-preserving dedup that should be `O(n)` time on average
-                perms
-                    .into_iter()
-                    .collect::<IndexSet<_>>()
-                    .into_iter()
-                    .collect()
+                    v
+                })
+                .collect(),
+        }
+    }
+
+    /// Every way to place this hint's two or more runs in a section of `length` cells, as the
+    /// start of each run, in the order [`Hint::permutations`] has always produced.
+    fn placements(&self, length: usize, gap: GapRule) -> Vec<Vec<usize>> {
+        let runs: Vec<usize> = self.runs().collect();
+        let gaps = vec![gap.min_gap; runs.len().saturating_sub(1)];
+        placements_in_legacy_order(&runs, &gaps, length)
+    }
+
+    /// The cells of a blank section of `length` cells that this hint decides without
+    /// enumerating its permutations, by comparing the leftmost and rightmost packings of the runs.
+    /// A run longer than the spare room always covers the middle of its range, and cells that no
+    /// run can reach are empty. Returns all `None` if the hint doesn't fit.
+    pub fn edge_forced(&self, length: usize) -> Vec<Option<Cell>> {
+        let mut forced = vec![None; length];
+        let Some(slack) = length.checked_sub(self.min_length()) else {
+            return forced;
+        };
+        let mut left_start = 0;
+        // The furthest any earlier run can reach.
+        let mut reach = 0;
+        for run in self.runs() {
+            if reach < left_start {
+                forced[reach..left_start].fill(Some(false));
             }
+            if run > slack {
+                forced[left_start + slack..left_start + run].fill(Some(true));
+            }
+            reach = left_start + slack + run;
+            left_start += run + 1;
         }
+        forced[reach..].fill(Some(false));
+        forced
     }
 
     pub fn brute_progress(&self, section: &[Option<Cell>]) -> Option<Vec<Option<Cell>>> {
@@ -81,28 +146,380 @@ impl Hint<'_> {
     }
 }
 
-pub fn perm_matches(x: &[Cell], y: &[Option<Cell>]) -> bool {
+/// The most placements [`placements`] reserves room for up front.
+const PLACEMENTS_PREALLOCATION_CAP: usize = 1 << 16;
+
+/// Every way to place `runs` in a section of `length` cells, where `gaps[k]` is the minimum
+/// space between runs `k` and `k + 1`, as the start of each run, in lexicographic order.
+///
+/// This steps through the placements like an odometer rather than recursing per run, so hints
+/// with many runs can't exhaust the stack.
+fn placements(runs: &[usize], gaps: &[usize], length: usize) -> Vec<Vec<usize>> {
+    debug_assert_eq!(gaps.len(), runs.len().saturating_sub(1));
+    // `min_suffix[j]` is the space needed by runs `j..`, including the gaps between them.
+    let mut min_suffix = vec![0; runs.len() + 1];
+    for j in (0..runs.len()).rev() {
+        let gap_after = gaps.get(j).copied().unwrap_or(0);
+        let needed = runs[j]
+            .checked_add(gap_after)
+            .and_then(|n| n.checked_add(min_suffix[j + 1]));
+        // Nothing that long fits in any section.
+        let Some(needed) = needed else {
+            return vec![];
+        };
+        min_suffix[j] = needed;
+    }
+    if min_suffix[0] > length {
+        return vec![];
+    }
+    let pack_from = |starts: &mut Vec<usize>, j: usize| {
+        for k in j..runs.len() {
+            let start = match k {
+                0 => 0,
+                _ => starts[k - 1] + runs[k - 1] + gaps[k - 1],
+            };
+            starts.truncate(k);
+            starts.push(start);
+        }
+    };
+    let mut starts = Vec::with_capacity(runs.len());
+    pack_from(&mut starts, 0);
+    // The count is only a hint: past the cap, let the `Vec` grow as placements are found.
+    let count = count_placements(length - min_suffix[0], runs.len());
+    let mut placements = Vec::with_capacity(count.min(PLACEMENTS_PREALLOCATION_CAP));
+    loop {
+        placements.push(starts.clone());
+        // Advance the rightmost run that still has room to move.
+        let Some(j) = (0..runs.len())
+            .rev()
+            .find(|&j| starts[j] + min_suffix[j] < length)
+        else {
+            return placements;
+        };
+        starts[j] += 1;
+        pack_from(&mut starts, j + 1);
+    }
+}
+
+/// Like [`placements`], but in the order the old recursive generator produced. It split each
+/// line after the second-to-last run, so placements are ordered by that run's start, then by the
+/// start of each earlier run from right to left, and finally by the last run's start. There must
+/// be at least two runs.
+fn placements_in_legacy_order(runs: &[usize], gaps: &[usize], length: usize) -> Vec<Vec<usize>> {
+    let n = runs.len();
+    debug_assert!(n >= 2 && gaps.len() == n - 1);
+    let min_length = runs
+        .iter()
+        .chain(gaps)
+        .try_fold(0usize, |total, &len| total.checked_add(len));
+    let Some(slack) = min_length.and_then(|min_length| length.checked_sub(min_length)) else {
+        return vec![];
+    };
+    // Packs runs `1..k` as far left as the run before each allows.
+    let pack_left = |starts: &mut [usize], k: usize| {
+        for j in 1..k {
+            starts[j] = starts[j - 1] + runs[j - 1] + gaps[j - 1];
+        }
+    };
+    let pack_last = |starts: &mut [usize]| {
+        starts[n - 1] = starts[n - 2] + runs[n - 2] + gaps[n - 2];
+    };
+    let mut starts = vec![0; n];
+    pack_left(&mut starts, n - 1);
+    pack_last(&mut starts);
+    let last_max = length - runs[n - 1];
+    // As far right as the second-to-last run goes while leaving room for the last.
+    let second_last_max = last_max - gaps[n - 2] - runs[n - 2];
+    let count = count_placements(slack, n);
+    let mut placements = Vec::with_capacity(count.min(PLACEMENTS_PREALLOCATION_CAP));
+    loop {
+        placements.push(starts.clone());
+        if starts[n - 1] < last_max {
+            starts[n - 1] += 1;
+            continue;
+        }
+        // Advance the leftmost run with room before the next one, then pack the runs to its
+        // left against the edge and the last run against the second-to-last.
+        let Some(k) = (0..n - 1).find(|&k| {
+            if k + 2 == n {
+                starts[k] < second_last_max
+            } else {
+                starts[k] + runs[k] + gaps[k] < starts[k + 1]
+            }
+        }) else {
+            return placements;
+        };
+        starts[k] += 1;
+        if k > 0 {
+            starts[0] = 0;
+            pack_left(&mut starts, k);
+        }
+        pack_last(&mut starts);
+    }
+}
+
+/// The number of ways to place `runs` runs with `slack` spare cells to share out between the
+/// gaps: the binomial coefficient `slack + runs` choose `runs`. Saturates at `usize::MAX`.
+fn count_placements(slack: usize, runs: usize) -> usize {
+    let n = slack.saturating_add(runs);
+    let k = runs.min(slack);
+    let mut count: usize = 1;
+    for i in 0..k {
+        // Exact at every step, since `count` is `n choose i` before it.
+        match count.checked_mul(n - i) {
+            Some(product) => count = product / (i + 1),
+            None => return usize::MAX,
+        }
+    }
+    count
+}
+
+/// Every arrangement of colored `runs` in a section of `length` cells, with the remaining cells
+/// set to `blank`.
+///
+/// Runs of the same color must be separated by at least one blank cell, but runs of different
+/// colors may touch.
+pub fn colored_permutations<C: CellLike>(
+    runs: &[(NonZeroUsize, C)],
+    length: usize,
+    blank: C,
+) -> Vec<Vec<C>> {
+    let lens: Vec<usize> = runs.iter().map(|(n, _)| n.get()).collect();
+    let gaps: Vec<usize> = runs
+        .windows(2)
+        .map(|pair| usize::from(pair[0].1 == pair[1].1))
+        .collect();
+    placements(&lens, &gaps, length)
+        .into_iter()
+        .map(|starts| {
+            let mut v = vec![blank; length];
+            for (&start, &(len, color)) in starts.iter().zip(runs) {
+                v[start..start + len.get()].fill(color);
+            }
+            v
+        })
+        .collect()
+}
+
+/// A run in a hint for puzzle variants where some runs only have a lower bound.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RunClue {
+    Exact(NonZeroUsize),
+    /// A run of this length or longer.
+    AtLeast(NonZeroUsize),
+}
+
+impl RunClue {
+    pub const fn min_len(self) -> usize {
+        match self {
+            Self::Exact(n) | Self::AtLeast(n) => n.get(),
+        }
+    }
+}
+
+/// Every arrangement of `clues` in a section of `length` cells, with runs separated by at least
+/// one empty cell. With only [`RunClue::Exact`] runs these are the same lines as
+/// [`Hint::permutations`], though not necessarily in the same order.
+pub fn extended_permutations(clues: &[RunClue], length: usize) -> Vec<Vec<Cell>> {
+    // Clues too long to add up fit in no section.
+    let min_total = clues
+        .iter()
+        .try_fold(clues.len().saturating_sub(1), |total, c| {
+            total.checked_add(c.min_len())
+        });
+    let Some(slack) = min_total.and_then(|min_total| length.checked_sub(min_total)) else {
+        return vec![];
+    };
+    // Every combination of run lengths that fits, paired with how much of the slack it uses.
+    let mut run_lens: Vec<(Vec<usize>, usize)> = vec![(vec![], 0)];
+    for &clue in clues {
+        run_lens = run_lens
+            .into_iter()
+            .flat_map(|(lens, used)| {
+                let max_extra = match clue {
+                    RunClue::Exact(_) => 0,
+                    RunClue::AtLeast(_) => slack - used,
+                };
+                (0..=max_extra).map(move |extra| {
+                    let mut lens = lens.clone();
+                    lens.push(clue.min_len() + extra);
+                    (lens, used + extra)
+                })
+            })
+            .collect();
+    }
+    let gaps = vec![1; clues.len().saturating_sub(1)];
+    let mut perms = vec![];
+    for (lens, _) in run_lens {
+        for starts in placements(&lens, &gaps, length) {
+            let mut v = vec![false; length];
+            for (&start, &len) in starts.iter().zip(&lens) {
+                v[start..start + len].fill(true);
+            }
+            perms.push(v);
+        }
+    }
+    perms
+}
+
+type HintLists = (Vec<Vec<NonZeroUsize>>, Vec<Vec<NonZeroUsize>>);
+
+/// Produces the row and column hints for every rotation and reflection of a puzzle, skipping
+/// variants that coincide. The original orientation always comes first.
+///
+/// The hints are transformed directly rather than through a solved board, so this also works for
+/// puzzles with several solutions.
+pub fn symmetry_variants(rows: &[Hint<'_>], cols: &[Hint<'_>]) -> Vec<HintLists> {
+    let to_vecs = |hints: &[Hint<'_>]| hints.iter().map(|h| h.lines.to_vec()).collect();
+    let reverse_each =
+        |hints: &mut Vec<Vec<NonZeroUsize>>| hints.iter_mut().for_each(|h| h.reverse());
+    let original: HintLists = (to_vecs(rows), to_vecs(cols));
+    let mut variants = vec![];
+    for transpose in [false, true] {
+        for mirror in [false, true] {
+            for flip in [false, true] {
+                let (mut rows, mut cols) = original.clone();
+                if transpose {
+                    (rows, cols) = (cols, rows);
+                }
+                if mirror {
+                    reverse_each(&mut rows);
+                    cols.reverse();
+                }
+                if flip {
+                    rows.reverse();
+                    reverse_each(&mut cols);
+                }
+                variants.push((rows, cols));
+            }
+        }
+    }
+    dedup(variants)
+}
+
+// awful, order-preserving dedup that should be `O(n)` time on average
+#[cfg(feature = "std")]
+fn dedup<T: Hash + Eq>(items: Vec<T>) -> Vec<T> {
+    items
+        .into_iter()
+        .collect::<IndexSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+// Without `std` there is no default hasher for `IndexSet`, so fall back to a quadratic scan.
+#[cfg(not(feature = "std"))]
+fn dedup<T: Hash + Eq>(items: Vec<T>) -> Vec<T> {
+    let mut unique: Vec<T> = Vec::with_capacity(items.len());
+    for item in items {
+        if !unique.contains(&item) {
+            unique.push(item);
+        }
+    }
+    unique
+}
+
+/// Converts a line from the gap encoding into the runs of its hint.
+///
+/// The gap encoding lists the length of every stretch of a line, alternating between empty and
+/// filled and starting with the empty stretch before the first run: `[gap, run, gap, ..., gap]`.
+/// The outer gaps may be zero, but runs and the gaps between them may not. Returns `None` if the
+/// encoding is malformed or its stretches don't add up to `total` cells.
+pub fn runs_from_gaps(gaps: &[usize], total: usize) -> Option<Vec<NonZeroUsize>> {
+    let sum = gaps
+        .iter()
+        .try_fold(0usize, |sum, &gap| sum.checked_add(gap));
+    if gaps.len().is_multiple_of(2) || sum != Some(total) {
+        return None;
+    }
+    let inner = gaps.get(1..gaps.len() - 1).unwrap_or_default();
+    if inner.contains(&0) {
+        return None;
+    }
+    inner
+        .iter()
+        .step_by(2)
+        .map(|&run| NonZeroUsize::new(run))
+        .collect()
+}
+
+/// Converts a line into the gap encoding described in [`runs_from_gaps`].
+pub fn gaps_from_line(line: &[Cell]) -> Vec<usize> {
+    let mut gaps = vec![0];
+    let mut filled = false;
+    for &cell in line {
+        if cell != filled {
+            filled = cell;
+            gaps.push(0);
+        }
+        *gaps.last_mut().unwrap() += 1;
+    }
+    if filled {
+        gaps.push(0);
+    }
+    gaps
+}
+
+/// The runs of filled cells in `line`, in order. A blank line has none.
+pub fn line_runs(line: &[Cell]) -> Vec<NonZeroUsize> {
+    line.split(|&cell| !cell)
+        .filter_map(|run| NonZeroUsize::new(run.len()))
+        .collect()
+}
+
+pub fn perm_matches<C: CellLike>(x: &[C], y: &[Option<C>]) -> bool {
+    first_mismatch(x, y).is_none()
+}
+
+/// The first position where `y` has a known cell that `x` disagrees with.
+pub fn first_mismatch<C: CellLike>(x: &[C], y: &[Option<C>]) -> Option<usize> {
     assert_eq!(x.len(), y.len());
-    !x.iter().zip(y).any(|(a, b)| b == &Some(!a))
+    x.iter()
+        .zip(y)
+        .position(|(&a, b)| b.is_some_and(|known| !a.compatible(known)))
 }
 
-pub fn sum_perms<T>(mut perms: T) -> Option<Vec<Option<Cell>>>
+/// No arrangement of a line is left, so the board it came from can't be completed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contradiction;
+
+/// The cells every arrangement in `perms` agrees on, with `None` where they differ.
+///
+/// Returns `None` if `perms` is empty. That always means the line has no valid arrangement, not
+/// that it's blank: a blank line still has one arrangement, with every cell empty.
+pub fn sum_perms<C, T>(mut perms: T) -> Option<Vec<Option<C>>>
 where
-    T: Iterator<Item = Vec<Cell>>,
+    C: CellLike,
+    T: Iterator<Item = Vec<C>>,
 {
-    let first = perms.next()?.into_iter().map(Some).collect();
-    Some(perms.fold(first, overlay))
+    let first = perms.next()?;
+    let mut sum: Vec<Option<C>> = first.iter().copied().map(Some).collect();
+    // Once two permutations disagree on a cell it stays unknown, so only the cells every
+    // permutation has agreed on so far need comparing, and we can stop once there are none.
+    let mut agreeing: Vec<usize> = (0..first.len()).collect();
+    for perm in perms {
+        if agreeing.is_empty() {
+            break;
+        }
+        assert_eq!(perm.len(), sum.len());
+        agreeing.retain(|&i| {
+            let agrees = perm[i] == first[i];
+            if !agrees {
+                sum[i] = None;
+            }
+            agrees
+        });
+    }
+    Some(sum)
 }
 
-fn overlay(mut dst: Vec<Option<Cell>>, src: Vec<Cell>) -> Vec<Option<Cell>> {
-    assert_eq!(dst.len(), src.len());
-    for (a, b) in dst.iter_mut().zip(src) {
-        *a = match (*a, b) {
-            (None, _) => None,
-            (Some(v1), v2) => (v1 == v2).then_some(v1),
-        };
-    }
-    dst
+/// Like [`sum_perms`], but names the empty case as a [`Contradiction`].
+pub fn sum_perms_result<C, T>(perms: T) -> Result<Vec<Option<C>>, Contradiction>
+where
+    C: CellLike,
+    T: Iterator<Item = Vec<C>>,
+{
+    sum_perms(perms).ok_or(Contradiction)
 }
 
 #[cfg(test)]
@@ -113,6 +530,83 @@ mod tests {
         NonZeroUsize::new(x).unwrap()
     }
 
+    // The straightforward fold `sum_perms` used to be, kept as a reference.
+    fn overlay(mut dst: Vec<Option<Cell>>, src: Vec<Cell>) -> Vec<Option<Cell>> {
+        assert_eq!(dst.len(), src.len());
+        for (a, b) in dst.iter_mut().zip(src) {
+            *a = match (*a, b) {
+                (None, _) => None,
+                (Some(v1), v2) => (v1 == v2).then_some(v1),
+            };
+        }
+        dst
+    }
+
+    #[test]
+    fn run_lengths() {
+        let lines = [u(3), u(1), u(2)];
+        let hint = Hint::new(&lines);
+        assert_eq!(hint.runs().collect::<Vec<_>>(), [3, 1, 2]);
+        assert_eq!(hint.runs().next_back(), Some(2));
+        assert_eq!(Hint::new(&[]).runs().len(), 0);
+    }
+
+    #[test]
+    fn permutation_counts() {
+        let hints: [&[NonZeroUsize]; 5] =
+            [&[], &[u(3)], &[u(2), u(3)], &[u(1), u(1), u(4)], &[u(9)]];
+        for lines in hints {
+            let hint = Hint::new(lines);
+            for length in 0..12 {
+                let count = hint.count_permutations(length);
+                assert_eq!(
+                    count,
+                    hint.permutations(length).len(),
+                    "{:?} in {}",
+                    lines,
+                    length
+                );
+            }
+        }
+        let huge = [u(usize::MAX), u(1)];
+        assert_eq!(Hint::new(&huge).total(), usize::MAX);
+        assert_eq!(Hint::new(&huge).min_length(), usize::MAX);
+        assert_eq!(Hint::new(&huge).count_permutations(5), 0);
+        let many = [u(1); 40];
+        assert_eq!(Hint::new(&many).count_permutations(200), usize::MAX);
+    }
+
+    #[test]
+    fn sum_contradiction() {
+        assert_eq!(
+            sum_perms_result(core::iter::empty::<Vec<Cell>>()),
+            Err(Contradiction)
+        );
+        let blank = Hint::new(&[]).permutations(2);
+        assert_eq!(
+            sum_perms_result(blank.into_iter()),
+            Ok(vec![Some(false), Some(false)])
+        );
+    }
+
+    #[test]
+    fn sum_matches_overlay() {
+        let hints: [&[NonZeroUsize]; 4] = [&[u(3)], &[u(2), u(3)], &[u(1), u(1), u(4)], &[u(7)]];
+        for lines in hints {
+            let perms = Hint::new(lines).permutations(8);
+            for n in 1..=perms.len() {
+                let subset = perms[..n].to_vec();
+                let mut rest = subset.clone().into_iter();
+                let first = rest.next().unwrap().into_iter().map(Some).collect();
+                assert_eq!(
+                    sum_perms(subset.into_iter()),
+                    Some(rest.fold(first, overlay))
+                );
+            }
+        }
+        assert_eq!(sum_perms(std::iter::empty::<Vec<Cell>>()), None);
+    }
+
     #[test]
     fn basic_perms() {
         let lines = [u(3)];
@@ -173,6 +667,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gap_rule_perms() {
+        let lines = [u(1), u(1)];
+        let h = Hint::new(&lines);
+        let touching = GapRule { min_gap: 0 };
+        assert_eq!(h.permutations_with_gap(2, touching), [[true, true]]);
+        assert_eq!(
+            h.permutations_with_gap(3, touching),
+            [
+                [true, true, false],
+                [true, false, true],
+                [false, true, true]
+            ]
+        );
+        let wide = GapRule { min_gap: 2 };
+        assert!(h.permutations_with_gap(3, wide).is_empty());
+        assert_eq!(
+            h.permutations_with_gap(4, wide),
+            [[true, false, false, true]]
+        );
+        for len in 0..10 {
+            assert_eq!(
+                h.permutations_with_gap(len, GapRule::default()),
+                h.permutations(len)
+            );
+        }
+    }
+
     #[test]
     fn large_perms_one() {
         let lines = [u(1)];
@@ -190,6 +712,234 @@ mod tests {
         //assert_eq!(h.permutations(500).len(), 124251);
     }
 
+    #[test]
+    fn symmetries() {
+        // An L-shaped tetromino has no symmetry, so all eight variants differ.
+        let (r0, r1, r2) = ([u(1)], [u(1)], [u(2)]);
+        let (c0, c1) = ([u(3)], [u(1)]);
+        let rows = [Hint::new(&r0), Hint::new(&r1), Hint::new(&r2)];
+        let cols = [Hint::new(&c0), Hint::new(&c1)];
+        let variants = symmetry_variants(&rows, &cols);
+        assert_eq!(variants.len(), 8);
+        assert_eq!(
+            variants[0],
+            (
+                vec![vec![u(1)], vec![u(1)], vec![u(2)]],
+                vec![vec![u(3)], vec![u(1)]]
+            )
+        );
+        assert!(variants.contains(&(
+            vec![vec![u(3)], vec![u(1)]],
+            vec![vec![u(1)], vec![u(1)], vec![u(2)]],
+        )));
+
+        // A plus sign is symmetric under every transformation.
+        let (outer, middle) = ([u(1)], [u(3)]);
+        let plus = [Hint::new(&outer), Hint::new(&middle), Hint::new(&outer)];
+        assert_eq!(symmetry_variants(&plus, &plus).len(), 1);
+    }
+
+    // The recursive generator `permutations_with_gap` used to be, kept to pin down its order.
+    fn recursive_perms(lines: &[NonZeroUsize], length: usize, gap: GapRule) -> Vec<Vec<Cell>> {
+        let Some((last, rest @ [_, ..])) = lines.split_last() else {
+            return Hint::new(lines).permutations_with_gap(length, gap);
+        };
+        let line_len = last.get();
+        if line_len > length {
+            return vec![];
+        }
+        let mut perms = vec![];
+        for i in 1..(length - line_len + 1).saturating_sub(gap.min_gap) {
+            let last_perms = Hint::new(&[*last]).permutations(length - i - gap.min_gap);
+            for mut p in recursive_perms(rest, i, gap) {
+                p.resize(i + gap.min_gap, false);
+                for lp in &last_perms {
+                    perms.push([p.as_slice(), lp].concat());
+                }
+            }
+        }
+        dedup(perms)
+    }
+
+    #[test]
+    fn legacy_perm_order() {
+        let hints: [&[NonZeroUsize]; 4] = [
+            &[u(1), u(2), u(1)],
+            &[u(2), u(1), u(1), u(3)],
+            &[u(1), u(1), u(1), u(1), u(1)],
+            &[u(3), u(2)],
+        ];
+        for lines in hints {
+            for min_gap in 0..3 {
+                let gap = GapRule { min_gap };
+                for length in 0..14 {
+                    assert_eq!(
+                        Hint::new(lines).permutations_with_gap(length, gap),
+                        recursive_perms(lines, length, gap),
+                        "{:?} in {} with gap {}",
+                        lines,
+                        length,
+                        min_gap
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn many_run_perms() {
+        let lines = [u(1); 30];
+        let h = Hint::new(&lines);
+        assert!(h.permutations(58).is_empty());
+        assert_eq!(h.permutations(59).len(), 1);
+        // 30 runs and 29 gaps leave one spare cell, which can go in any of the 31 gaps.
+        assert_eq!(h.permutations(60).len(), 31);
+    }
+
+    #[test]
+    fn overflowing_runs() {
+        let lines = [u(usize::MAX), u(1)];
+        assert!(Hint::new(&lines).permutations(5).is_empty());
+        let gap = GapRule {
+            min_gap: usize::MAX,
+        };
+        assert!(Hint::new(&[u(1), u(1)])
+            .permutations_with_gap(5, gap)
+            .is_empty());
+    }
+
+    #[test]
+    fn gap_encoding() {
+        let line = [false, true, true, false, false, true, false];
+        let gaps = gaps_from_line(&line);
+        assert_eq!(gaps, [1, 2, 2, 1, 1]);
+        assert_eq!(runs_from_gaps(&gaps, 7), Some(vec![u(2), u(1)]));
+        assert_eq!(gaps_from_line(&[true, true]), [0, 2, 0]);
+        assert_eq!(runs_from_gaps(&[0, 2, 0], 2), Some(vec![u(2)]));
+        assert_eq!(gaps_from_line(&[false; 3]), [3]);
+        assert_eq!(runs_from_gaps(&[3], 3), Some(vec![]));
+        assert_eq!(runs_from_gaps(&gaps, 8), None);
+        assert_eq!(runs_from_gaps(&[1, 2, 0, 1, 1], 5), None);
+        assert_eq!(runs_from_gaps(&[1, 2], 3), None);
+        assert_eq!(runs_from_gaps(&[usize::MAX, 1, 1], 1), None);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Color {
+        Blank,
+        Red,
+        Blue,
+    }
+
+    impl CellLike for Color {
+        fn is_filled(self) -> bool {
+            self != Self::Blank
+        }
+    }
+
+    #[test]
+    fn custom_cells() {
+        use Color::*;
+        let perms = vec![vec![Red, Blue, Blank], vec![Red, Red, Blank]];
+        assert!(perm_matches(&perms[0], &[Some(Red), None, None]));
+        assert!(!perm_matches(&perms[1], &[None, Some(Blue), None]));
+        assert_eq!(
+            sum_perms(perms.into_iter()),
+            Some(vec![Some(Red), None, Some(Blank)])
+        );
+    }
+
+    #[test]
+    fn runs() {
+        let line = [true, true, false, false, true, false, true, true, true];
+        assert_eq!(line_runs(&line), [u(2), u(1), u(3)]);
+        assert_eq!(line_runs(&[false, true]), [u(1)]);
+        assert!(line_runs(&[false; 4]).is_empty());
+        assert!(line_runs(&[]).is_empty());
+        for perm in Hint::new(&[u(1), u(2)]).permutations(6) {
+            assert_eq!(line_runs(&perm), [u(1), u(2)]);
+        }
+    }
+
+    #[test]
+    fn at_least_runs() {
+        use RunClue::*;
+        let x = true;
+        let o = false;
+        assert_eq!(
+            extended_permutations(&[AtLeast(u(2))], 3),
+            [[x, x, o], [o, x, x], [x, x, x]]
+        );
+        let mixed = extended_permutations(&[Exact(u(1)), AtLeast(u(2))], 5);
+        assert_eq!(
+            mixed,
+            [
+                [x, o, x, x, o],
+                [x, o, o, x, x],
+                [o, x, o, x, x],
+                [x, o, x, x, x]
+            ]
+        );
+        assert!(extended_permutations(&[AtLeast(u(4))], 3).is_empty());
+        let huge = [AtLeast(u(usize::MAX)), AtLeast(u(1))];
+        assert!(extended_permutations(&huge, 3).is_empty());
+        assert_eq!(extended_permutations(&[], 2), [[o, o]]);
+
+        let lines = [u(2), u(1), u(1)];
+        let mut exact = extended_permutations(&lines.map(Exact), 7);
+        let mut plain = Hint::new(&lines).permutations(7);
+        exact.sort();
+        plain.sort();
+        assert_eq!(exact, plain);
+    }
+
+    #[test]
+    fn edge_overlap() {
+        let x = Some(true);
+        let o = Some(false);
+        assert_eq!(
+            Hint::new(&[u(4)]).edge_forced(6),
+            [None, None, x, x, None, None]
+        );
+        assert_eq!(Hint::new(&[u(2), u(1)]).edge_forced(4), [x, x, o, x]);
+        assert_eq!(Hint::new(&[]).edge_forced(2), [o, o]);
+        assert_eq!(Hint::new(&[u(3)]).edge_forced(2), [None, None]);
+        // Everything it finds, the full intersection finds too.
+        let hints = [
+            vec![u(3)],
+            vec![u(1), u(2)],
+            vec![u(4), u(1)],
+            vec![u(2), u(2), u(1)],
+        ];
+        for lines in &hints {
+            let hint = Hint::new(lines);
+            for length in hint.min_length()..10 {
+                let full = hint.brute_progress(&vec![None; length]).unwrap();
+                for (edge, full) in hint.edge_forced(length).into_iter().zip(full) {
+                    assert!(edge.is_none() || edge == full, "{:?} in {}", lines, length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn colored_adjacency() {
+        use Color::*;
+        let touching = colored_permutations(&[(u(1), Red), (u(1), Blue)], 3, Blank);
+        assert_eq!(
+            touching,
+            [[Red, Blue, Blank], [Red, Blank, Blue], [Blank, Red, Blue]]
+        );
+        let separated = colored_permutations(&[(u(1), Red), (u(1), Red)], 3, Blank);
+        assert_eq!(separated, [[Red, Blank, Red]]);
+        assert!(colored_permutations(&[(u(2), Red), (u(1), Red)], 3, Blank).is_empty());
+        assert_eq!(
+            colored_permutations(&[(u(2), Red), (u(1), Blue)], 3, Blank),
+            [[Red, Red, Blue]]
+        );
+        assert_eq!(colored_permutations(&[], 2, Blank), [[Blank, Blank]]);
+    }
+
     #[test]
     fn basic_progress() {
         let lines = [u(3)];
@@ -1,20 +1,47 @@
 #![warn(unsafe_op_in_unsafe_fn)]
 #![allow(dead_code)]
+#![cfg_attr(fuzzing, no_main)]
 
+// `board`, `cell`, `event` and `hint` only rely on `core` and `alloc`, so the solver core can be
+// lifted into a `no_std` environment by building without the default `std` feature. The crate in
+// `no_std/` builds them on their own to check this.
+extern crate alloc;
+
+#[cfg(feature = "gif")]
+mod animation;
 mod board;
 mod cell;
+mod event;
 mod hint;
+mod parse;
+mod puzzle;
+#[cfg(feature = "spill")]
+mod spill;
+#[cfg(feature = "tui")]
+mod tui;
+
+#[cfg(fuzzing)]
+#[path = "../fuzz/fuzz_targets/solve.rs"]
+mod fuzz_solve;
 
 use board::Board;
 use cell::Cell;
+use event::{Axis, SolveEvent};
 use hint::Hint;
+use parse::{HintParseError, Palette};
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::HashSet;
 use std::fmt;
+use std::io::{self, Write};
+use std::mem;
 use std::num::NonZeroUsize;
+use std::ops::ControlFlow;
 use std::pin::Pin;
-use std::time::Instant;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 struct HintHolder<'a> {
-    _source: Pin<Vec<Vec<NonZeroUsize>>>,
+    source: Pin<Vec<Vec<NonZeroUsize>>>,
     individuals: Vec<Hint<'a>>,
 }
 
@@ -27,39 +54,376 @@ impl<'a> HintHolder<'a> {
             .map(|v| Hint::new(unsafe { &*(v.as_slice() as *const [NonZeroUsize]) }))
             .collect();
         Self {
-            _source: source,
+            source,
             individuals,
         }
     }
     pub fn get(&'a self) -> &'a [Hint<'a>] {
         &self.individuals
     }
+    /// The hint for line `i`, or `None` if there are fewer lines.
+    pub fn hint(&'a self, i: usize) -> Option<&'a Hint<'a>> {
+        self.individuals.get(i)
+    }
+    /// The runs of every line. Hints are stored in this form however they were written, so two
+    /// holders describe the same lines exactly when their canonical forms are equal.
+    pub fn canonical(&self) -> Vec<Vec<NonZeroUsize>> {
+        self.source.to_vec()
+    }
+}
+
+impl FromIterator<Vec<NonZeroUsize>> for HintHolder<'_> {
+    fn from_iter<I: IntoIterator<Item = Vec<NonZeroUsize>>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// The hints of a colored puzzle: every line's runs, each with its color, ready for
+/// [`hint::colored_permutations`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ColorHintHolder<C> {
+    lines: Vec<Vec<(NonZeroUsize, C)>>,
+}
+
+impl<C> ColorHintHolder<C> {
+    pub fn get(&self) -> &[Vec<(NonZeroUsize, C)>] {
+        &self.lines
+    }
 }
 
 type GuessBoard = Board<Option<Cell>>;
-type SectionPerms = Vec<Vec<Vec<Cell>>>;
 
+/// The most solutions [`Picross::forced_cells`] will compare before giving up on finding more.
+const FORCED_CELLS_CAP: usize = 10_000;
+/// The most solutions [`puzzles_equivalent`] will enumerate for each puzzle.
+const EQUIVALENCE_CAP: usize = 1_000;
+/// The most solutions [`Picross::distinct_pictures`] will enumerate.
+const DISTINCT_PICTURES_CAP: usize = 1_000;
+/// The arrangements of every line along one axis: for each line, every way of filling it that its
+/// hint allows, each as one cell per position.
+pub type SectionPerms = Vec<Vec<Vec<Cell>>>;
+type Snapshot = (GuessBoard, SectionPerms, SectionPerms);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StrictError {
+    /// Line logic got stuck with cells still undetermined.
+    RequiresGuessing,
+    /// Some line has no arrangement consistent with the rest of the board.
+    Contradiction,
+}
+
+/// Why line logic found a puzzle unsolvable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Explanation {
+    pub axis: Axis,
+    pub index: usize,
+    /// The known cells of the line, by position, that rule out its arrangements. Every
+    /// arrangement the hint allows disagrees with at least one of them.
+    pub cells: Vec<(usize, Cell)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PrefillError {
+    /// A prefilled cell lies outside the grid.
+    OutOfBounds { x: usize, y: usize },
+    /// The same cell was prefilled with both values.
+    Conflict { x: usize, y: usize },
+    /// The prefilled cells leave the given line with no valid arrangement.
+    Contradiction { axis: Axis, index: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PermsError {
+    /// There are permutations for a different number of lines than the board has along `axis`.
+    LineCount {
+        axis: Axis,
+        expected: usize,
+        actual: usize,
+    },
+    /// An arrangement of the given line has the wrong number of cells.
+    PermLength {
+        axis: Axis,
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HintError {
+    /// The line's runs and the gaps between them are longer than the line itself.
+    Impossible { axis: Axis, index: usize },
+}
+
+/// Returned by [`Picross::search`] when its callback stops the search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Interrupted;
+
+/// How many solutions a puzzle has, as reported by [`Picross::solution_cardinality`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Cardinality {
+    None,
+    Unique(Board<Cell>),
+    /// Two or more; the search stops at the second.
+    Multiple,
+}
+
+/// Which lines [`Picross`] examines first in each propagation pass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum AxisOrder {
+    #[default]
+    RowsFirst,
+    ColsFirst,
+    /// Swap the order on every pass, starting with rows.
+    Alternate,
+}
+
+/// A line-solving nonogram solver that falls back to guessing when line logic gets stuck.
+///
+/// The search is deterministic: the same hints always yield the same solutions in the same
+/// order. Every ordering comes from `Vec`s and insertion-ordered sets, and hashed maps are only
+/// ever used for lookups, never iterated.
 #[derive(Clone, Debug, Default)]
 struct Picross {
+    row_hints: Vec<Vec<NonZeroUsize>>,
+    col_hints: Vec<Vec<NonZeroUsize>>,
+    // The unfiltered permutations of every line, kept so the search can restart without
+    // regenerating them.
+    fresh_rows_perms: SectionPerms,
+    fresh_cols_perms: SectionPerms,
     board: GuessBoard,
     rows_perms: SectionPerms,
     cols_perms: SectionPerms,
-    backtrack: Vec<(GuessBoard, SectionPerms, SectionPerms)>,
+    // Lines with a cell that a crossing line determined since they were last examined. Passes
+    // after the first skip clean lines, since filtering them again can't rule anything out.
+    dirty_rows: Vec<bool>,
+    dirty_cols: Vec<bool>,
+    backtrack: Vec<Snapshot>,
+    // The cell guessed filled for each snapshot in `backtrack`, whose alternative is that it's
+    // empty.
+    guesses: Vec<(usize, usize)>,
+    // Snapshots that were restored from or abandoned, kept so their buffers can be reused by
+    // later bifurcations instead of allocating new ones.
+    #[cfg(feature = "pool")]
+    pool: Vec<Snapshot>,
+    // Where snapshots go once `backtrack` grows past the spill threshold.
+    #[cfg(feature = "spill")]
+    spill: spill::Spill,
     pub num_backtracks: usize,
+    pub num_passes: usize,
+    axis_order: AxisOrder,
+    // Whether solutions must have a single orthogonally connected filled region.
+    require_connected: bool,
+    row_deductions: usize,
+    col_deductions: usize,
+    // The line that most recently ran out of permutations.
+    contradiction: Option<(Axis, usize)>,
+    // Set once the search has run out of alternatives, so later searches return `None` at once.
+    exhausted: bool,
+    log: Option<Vec<SolveEvent>>,
 }
 
 impl Picross {
+    /// Like [`Picross::new`], but fails up front if some hint can't fit in its line, rather than
+    /// leaving the solver to find the puzzle has no solution.
+    pub fn try_new(row_hints: &[Hint<'_>], col_hints: &[Hint<'_>]) -> Result<Self, HintError> {
+        let lines = [
+            (Axis::Row, row_hints, col_hints.len()),
+            (Axis::Col, col_hints, row_hints.len()),
+        ];
+        for (axis, hints, len) in lines {
+            if let Some(index) = hints.iter().position(|hint| hint.min_length() > len) {
+                return Err(HintError::Impossible { axis, index });
+            }
+        }
+        Ok(Self::new(row_hints, col_hints))
+    }
+    /// Builds a square puzzle whose rows and columns share `hints`, as in any picture that is
+    /// symmetric about its main diagonal.
+    pub fn new_square(hints: &[Hint<'_>]) -> Self {
+        debug_assert!(
+            hints_fit(hints, hints.len()),
+            "square hints don't fit in {} cells",
+            hints.len()
+        );
+        Self::new(hints, hints)
+    }
     pub fn new(row_hints: &[Hint<'_>], col_hints: &[Hint<'_>]) -> Self {
+        // The board's dimensions come from the hint counts, so there is exactly one set of
+        // permutations per board line whatever the hints say.
         let (w, h) = (col_hints.len(), row_hints.len());
-        let get_perms =
-            |hints: &[Hint<'_>], len| hints.iter().map(|hint| hint.permutations(len)).collect();
+        // Hints that don't fit make an unsolvable but valid puzzle, unless swapping the axes would
+        // make them fit, which is far more likely to be a mistake.
+        debug_assert!(
+            hints_fit(row_hints, w) && hints_fit(col_hints, h)
+                || !(hints_fit(row_hints, h) && hints_fit(col_hints, w)),
+            "hints don't fit a {}x{} puzzle but would if rows and columns were swapped",
+            w,
+            h
+        );
+        // Repeated hints (common in symmetric pictures) share one generated permutation set,
+        // which is then copied so each line can be filtered independently.
+        let get_perms = |hints: &[Hint<'_>], len| {
+            // Only used for lookups, so its randomized iteration order never leaks out.
+            let mut first_with: HashMap<&[NonZeroUsize], usize> = HashMap::new();
+            let mut perms: SectionPerms = Vec::with_capacity(hints.len());
+            for (i, hint) in hints.iter().enumerate() {
+                let line_perms = match first_with.entry(hint.lines()) {
+                    Entry::Occupied(first) => perms[*first.get()].clone(),
+                    Entry::Vacant(first) => {
+                        first.insert(i);
+                        hint.permutations(len)
+                    }
+                };
+                perms.push(line_perms);
+            }
+            perms
+        };
+        let get_lines =
+            |hints: &[Hint<'_>]| hints.iter().map(|hint| hint.lines().to_vec()).collect();
+        let (rows_perms, cols_perms) = (get_perms(row_hints, w), get_perms(col_hints, h));
+        Self::from_parts(
+            get_lines(row_hints),
+            get_lines(col_hints),
+            rows_perms,
+            cols_perms,
+        )
+    }
+    /// Builds a puzzle from already generated permutations, such as those of another solver's
+    /// [`Picross::row_permutations`] and [`Picross::col_permutations`], to skip generating them
+    /// again. `dims` is the board's `(width, height)`.
+    ///
+    /// Each line's arrangements should all come from the same hint, since the hint is read back
+    /// from the first one. They're otherwise used as given, including their order.
+    pub fn from_perms(
+        dims: (usize, usize),
+        rows_perms: SectionPerms,
+        cols_perms: SectionPerms,
+    ) -> Result<Self, PermsError> {
+        let (w, h) = dims;
+        for (axis, perms, lines, len) in [
+            (Axis::Row, &rows_perms, h, w),
+            (Axis::Col, &cols_perms, w, h),
+        ] {
+            if perms.len() != lines {
+                let actual = perms.len();
+                return Err(PermsError::LineCount {
+                    axis,
+                    expected: lines,
+                    actual,
+                });
+            }
+            for (index, line) in perms.iter().enumerate() {
+                if let Some(perm) = line.iter().find(|perm| perm.len() != len) {
+                    let actual = perm.len();
+                    return Err(PermsError::PermLength {
+                        axis,
+                        index,
+                        expected: len,
+                        actual,
+                    });
+                }
+            }
+        }
+        let get_lines = |perms: &SectionPerms| {
+            perms
+                .iter()
+                .map(|line| {
+                    line.first()
+                        .map_or_else(Vec::new, |perm| hint::line_runs(perm))
+                })
+                .collect()
+        };
+        Ok(Self::from_parts(
+            get_lines(&rows_perms),
+            get_lines(&cols_perms),
+            rows_perms,
+            cols_perms,
+        ))
+    }
+    fn from_parts(
+        row_hints: Vec<Vec<NonZeroUsize>>,
+        col_hints: Vec<Vec<NonZeroUsize>>,
+        rows_perms: SectionPerms,
+        cols_perms: SectionPerms,
+    ) -> Self {
+        let (w, h) = (cols_perms.len(), rows_perms.len());
         Self {
+            row_hints,
+            col_hints,
+            fresh_rows_perms: rows_perms.clone(),
+            fresh_cols_perms: cols_perms.clone(),
             board: Board::new_default(w, h),
-            rows_perms: get_perms(row_hints, w),
-            cols_perms: get_perms(col_hints, h),
+            rows_perms,
+            cols_perms,
+            dirty_rows: vec![true; h],
+            dirty_cols: vec![true; w],
             backtrack: vec![],
+            guesses: vec![],
+            #[cfg(feature = "pool")]
+            pool: vec![],
+            #[cfg(feature = "spill")]
+            spill: spill::Spill::default(),
             num_backtracks: 0,
+            num_passes: 0,
+            axis_order: AxisOrder::default(),
+            require_connected: false,
+            row_deductions: 0,
+            col_deductions: 0,
+            contradiction: None,
+            exhausted: false,
+            log: None,
+        }
+    }
+}
+
+impl Picross {
+    /// Creates a puzzle with some cells already known, such as scaffolding given to a student.
+    ///
+    /// The permutations of every line are filtered against those cells up front, so a prefilled
+    /// cell that no arrangement of its row or column allows is reported here. These cells are
+    /// not restored by [`Picross::reset`].
+    pub fn from_partial(
+        row_hints: &[Hint<'_>],
+        col_hints: &[Hint<'_>],
+        prefilled: &[(usize, usize, Cell)],
+    ) -> Result<Self, PrefillError> {
+        let mut picross = Self::new(row_hints, col_hints);
+        for &(x, y, value) in prefilled {
+            let cell = picross
+                .board
+                .pos_checked_mut(x, y)
+                .ok_or(PrefillError::OutOfBounds { x, y })?;
+            if *cell == Some(!value) {
+                return Err(PrefillError::Conflict { x, y });
+            }
+            *cell = Some(value);
+        }
+        for (y, row_perms) in picross.rows_perms.iter_mut().enumerate() {
+            let row = picross.board.row(y);
+            row_perms.retain(|perm| hint::perm_matches(perm, row));
+            if row_perms.is_empty() {
+                let (axis, index) = (Axis::Row, y);
+                return Err(PrefillError::Contradiction { axis, index });
+            }
+        }
+        for (x, col_perms) in picross.cols_perms.iter_mut().enumerate() {
+            let col = picross.board.col(x);
+            col_perms.retain(|perm| hint::perm_matches(perm, &col));
+            if col_perms.is_empty() {
+                let (axis, index) = (Axis::Col, x);
+                return Err(PrefillError::Contradiction { axis, index });
+            }
         }
+        Ok(picross)
+    }
+    /// Solves a puzzle starting from some known cells. See [`Picross::from_partial`].
+    pub fn solve_from_picture(
+        row_hints: &[Hint<'_>],
+        col_hints: &[Hint<'_>],
+        prefilled: &[(usize, usize, Cell)],
+    ) -> Result<Option<Board<Cell>>, PrefillError> {
+        Ok(Self::from_partial(row_hints, col_hints, prefilled)?.find_solution())
     }
 }
 
@@ -70,6 +434,147 @@ impl Picross {
     pub const fn height(&self) -> usize {
         self.board.height()
     }
+    /// Whether the puzzle has no cells at all, i.e. zero rows or zero columns.
+    pub const fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+    pub const fn cell_count(&self) -> usize {
+        self.width() * self.height()
+    }
+    /// The fraction of cells currently determined, from `0.0` to `1.0`. A puzzle with no cells
+    /// counts as fully determined.
+    pub fn progress(&self) -> f32 {
+        if self.is_empty() {
+            return 1.0;
+        }
+        let known = self.board.as_slice().iter().filter(|c| c.is_some()).count();
+        known as f32 / self.cell_count() as f32
+    }
+    /// The arrangements of row `y` that are still possible, or an empty slice if `y` is out of
+    /// bounds. Before solving, this is every arrangement the hint allows.
+    pub fn row_permutations(&self, y: usize) -> &[Vec<Cell>] {
+        self.rows_perms.get(y).map_or(&[], Vec::as_slice)
+    }
+    /// The column analog of [`Picross::row_permutations`].
+    pub fn col_permutations(&self, x: usize) -> &[Vec<Cell>] {
+        self.cols_perms.get(x).map_or(&[], Vec::as_slice)
+    }
+    pub fn set_axis_order(&mut self, order: AxisOrder) {
+        self.axis_order = order;
+    }
+    /// Only accept solutions whose filled cells form one orthogonally connected region, as some
+    /// puzzle variants require. After every pass, the search abandons any branch whose filled
+    /// cells are already cut off from each other by empty cells.
+    pub fn set_require_connected(&mut self, require_connected: bool) {
+        self.require_connected = require_connected;
+    }
+    /// Copies the puzzle as if it had just been built from its hints, without the board, the
+    /// backtrack stack or the filtered permutations of the search so far. Settings like the axis
+    /// order carry over, and so does whether logging is enabled, though the log starts empty.
+    pub fn clone_fresh(&self) -> Self {
+        let (w, h) = (self.width(), self.height());
+        Self {
+            row_hints: self.row_hints.clone(),
+            col_hints: self.col_hints.clone(),
+            fresh_rows_perms: self.fresh_rows_perms.clone(),
+            fresh_cols_perms: self.fresh_cols_perms.clone(),
+            board: Board::new_default(w, h),
+            rows_perms: self.fresh_rows_perms.clone(),
+            cols_perms: self.fresh_cols_perms.clone(),
+            dirty_rows: vec![true; h],
+            dirty_cols: vec![true; w],
+            backtrack: vec![],
+            guesses: vec![],
+            #[cfg(feature = "pool")]
+            pool: vec![],
+            #[cfg(feature = "spill")]
+            spill: {
+                let mut spill = spill::Spill::default();
+                spill.set_threshold(self.spill.threshold());
+                spill
+            },
+            num_backtracks: 0,
+            num_passes: 0,
+            axis_order: self.axis_order,
+            require_connected: self.require_connected,
+            row_deductions: 0,
+            col_deductions: 0,
+            contradiction: None,
+            exhausted: false,
+            log: self.log.as_ref().map(|_| vec![]),
+        }
+    }
+    /// Discards all solving progress so the next search starts from an empty board.
+    pub fn reset(&mut self) {
+        self.board = Board::new_default(self.width(), self.height());
+        self.mark_all_dirty();
+        self.rows_perms.clone_from(&self.fresh_rows_perms);
+        self.cols_perms.clone_from(&self.fresh_cols_perms);
+        #[cfg(feature = "pool")]
+        self.pool.append(&mut self.backtrack);
+        self.backtrack.clear();
+        #[cfg(feature = "spill")]
+        self.spill
+            .clear()
+            .expect("failed to clear the backtrack spill file");
+        self.guesses.clear();
+        self.exhausted = false;
+        self.num_backtracks = 0;
+        self.num_passes = 0;
+        self.row_deductions = 0;
+        self.col_deductions = 0;
+    }
+    /// Replaces the hint for row `y`, regenerating only that row's permutations.
+    ///
+    /// Any deduction made so far may have depended on the old hint, so the search restarts from
+    /// an empty board as with [`Picross::reset`].
+    pub fn update_row_hint(&mut self, y: usize, hint: &Hint<'_>) {
+        assert!(y < self.height(), "row {} out of bounds", y);
+        self.row_hints[y] = hint.lines().to_vec();
+        self.fresh_rows_perms[y] = hint.permutations(self.width());
+        self.reset();
+    }
+    /// Replaces the hint for column `x`. See [`Picross::update_row_hint`].
+    pub fn update_col_hint(&mut self, x: usize, hint: &Hint<'_>) {
+        assert!(x < self.width(), "column {} out of bounds", x);
+        self.col_hints[x] = hint.lines().to_vec();
+        self.fresh_cols_perms[x] = hint.permutations(self.height());
+        self.reset();
+    }
+    /// A cheap sanity check that rejects some unsolvable puzzles without running the solver.
+    ///
+    /// Both hint lists count the same filled cells, so their totals must agree, and every hint
+    /// must fit within its line. Passing this check does not guarantee a solution exists.
+    pub fn quick_feasibility(&self) -> bool {
+        let fits = |hints: &[Vec<NonZeroUsize>], len| {
+            hints
+                .iter()
+                .all(|lines| Hint::new(lines).min_length() <= len)
+        };
+        self.filled_cell_balance().is_ok()
+            && fits(&self.row_hints, self.width())
+            && fits(&self.col_hints, self.height())
+    }
+    /// Returns the number of filled cells both hint lists agree on, or `(row_total, col_total)`
+    /// if they disagree.
+    pub fn filled_cell_balance(&self) -> Result<usize, (usize, usize)> {
+        let total = |hints: &[Vec<NonZeroUsize>]| -> usize {
+            hints
+                .iter()
+                .map(|lines| Hint::new(lines).total())
+                .fold(0, usize::saturating_add)
+        };
+        let (row_total, col_total) = (total(&self.row_hints), total(&self.col_hints));
+        if row_total == col_total {
+            Ok(row_total)
+        } else {
+            Err((row_total, col_total))
+        }
+    }
+    /// Collects every remaining solution.
+    ///
+    /// This runs until the search is exhausted, which for a highly ambiguous puzzle may never
+    /// happen in practice. Prefer [`Picross::get_solutions_capped`] for untrusted input.
     pub fn get_solutions(&mut self) -> Vec<Board<Cell>> {
         let mut solutions = vec![];
         while let Some(solution) = self.find_solution() {
@@ -77,108 +582,723 @@ impl Picross {
         }
         solutions
     }
-    pub fn find_solution(&mut self) -> Option<Board<Cell>> {
+    /// Collects at most `max` solutions, stopping the search as soon as the cap is reached.
+    pub fn get_solutions_capped(&mut self, max: usize) -> Vec<Board<Cell>> {
+        let mut solutions = vec![];
+        while solutions.len() < max {
+            match self.find_solution() {
+                Some(solution) => solutions.push(solution),
+                None => break,
+            }
+        }
+        solutions
+    }
+    /// Collects at most `max` solutions, giving up once `budget` has elapsed. The flag is `true`
+    /// if either limit cut the search short, and `false` if every solution was found.
+    ///
+    /// The time limit is checked between propagation passes, so a single slow pass can overrun
+    /// it. Reaching `max` counts as truncation even if no further solution exists.
+    #[cfg(feature = "std")]
+    pub fn get_solutions_bounded(
+        &mut self,
+        max: usize,
+        budget: Duration,
+    ) -> (Vec<Board<Cell>>, bool) {
+        let deadline = Instant::now().checked_add(budget);
+        let mut solutions = vec![];
+        while solutions.len() < max {
+            let found = self.search(|_| match deadline {
+                Some(deadline) if Instant::now() >= deadline => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            });
+            match found {
+                Ok(Some(solution)) => solutions.push(solution),
+                Ok(None) => return (solutions, false),
+                Err(Interrupted) => return (solutions, true),
+            }
+        }
+        (solutions, true)
+    }
+    /// Restarts the search and collects every solution, exploring both sides of the first guess
+    /// on separate threads. Solutions come in the same order as from [`Picross::get_solutions`].
+    #[cfg(feature = "rayon")]
+    pub fn get_solutions_parallel(&mut self) -> Vec<Board<Cell>> {
+        self.reset();
         let mut first_run = true;
         loop {
-            let mut progressed = false;
-            let mut backtracked = false;
-            for (y, row_perms) in self.rows_perms.iter_mut().enumerate() {
-                let row = self.board.row(y);
-                let old_len = row_perms.len();
-                row_perms.retain(|perm| hint::perm_matches(perm, row));
-                if row_perms.len() < old_len || first_run {
-                    let new_row = match hint::sum_perms(row_perms.clone().into_iter()) {
-                        Some(r) => r,
-                        None => {
-                            (self.board, self.rows_perms, self.cols_perms) =
-                                self.backtrack.pop()?;
-                            backtracked = true;
-                            break;
-                        }
-                    };
-                    if new_row != row {
-                        progressed = true;
-                        self.board.set_row(y, new_row);
-                    }
-                }
+            let propagated = self
+                .propagate(first_run)
+                .filter(|_| !self.require_connected || self.board.filled_could_connect());
+            match propagated {
+                Some(true) => first_run = false,
+                Some(false) => break,
+                None => return vec![],
             }
-            if backtracked {
-                continue;
+        }
+        let cell = self
+            .board
+            .iter_positions()
+            .find_map(|(x, y, v)| v.is_none().then_some((x, y)));
+        let Some((x, y)) = cell else {
+            // Line logic alone solved it.
+            return self
+                .solved_board()
+                .filter(|_| self.lines_consistent())
+                .into_iter()
+                .collect();
+        };
+        let (mut filled, mut empty) = (self.clone(), self.clone());
+        for (branch, value) in [(&mut filled, true), (&mut empty, false)] {
+            *branch.board.pos_mut(x, y) = Some(value);
+            (branch.dirty_rows[y], branch.dirty_cols[x]) = (true, true);
+        }
+        let (mut solutions, rest) =
+            rayon::join(|| filled.get_solutions(), || empty.get_solutions());
+        solutions.extend(rest);
+        solutions
+    }
+    /// Checks `board` against the hints directly, without the solver's permutations, and returns
+    /// the first line that doesn't match. If the dimensions differ, that is the first line that
+    /// only one of the board and the hints has.
+    pub fn check_solution(&self, board: &Board<Cell>) -> Result<(), (Axis, usize)> {
+        let lines = [
+            (Axis::Row, board.height(), self.row_hints.len()),
+            (Axis::Col, board.width(), self.col_hints.len()),
+        ];
+        for (axis, board_lines, hint_lines) in lines {
+            if board_lines != hint_lines {
+                return Err((axis, board_lines.min(hint_lines)));
             }
-
-            for (x, col_perms) in self.cols_perms.iter_mut().enumerate() {
-                let col = self.board.col(x);
-                let old_len = col_perms.len();
-                col_perms.retain(|perm| hint::perm_matches(perm, &col));
-                if col_perms.len() < old_len || first_run {
-                    let new_col = match hint::sum_perms(col_perms.clone().into_iter()) {
-                        Some(r) => r,
-                        None => {
-                            (self.board, self.rows_perms, self.cols_perms) =
-                                self.backtrack.pop()?;
-                            backtracked = true;
-                            break;
-                        }
-                    };
-                    if new_col != col {
-                        progressed = true;
-                        self.board.set_col(x, new_col);
-                    }
-                }
+        }
+        let check = |axis, hints: &[Vec<NonZeroUsize>], lines: &mut dyn Iterator<Item = Vec<_>>| {
+            let mut lines = lines.map(|line| hint::line_runs(&line));
+            match hints
+                .iter()
+                .position(|hint| lines.next().as_ref() != Some(hint))
+            {
+                Some(index) => Err((axis, index)),
+                None => Ok(()),
             }
-            if backtracked {
-                continue;
+        };
+        let mut rows = (0..board.height()).map(|y| board.row(y).to_vec());
+        let mut cols = (0..board.width()).map(|x| board.col(x));
+        check(Axis::Row, &self.row_hints, &mut rows)?;
+        check(Axis::Col, &self.col_hints, &mut cols)
+    }
+    /// Restarts the search and counts its solutions, treating rotations and reflections of one
+    /// picture as the same. Only the first [`DISTINCT_PICTURES_CAP`] solutions are considered.
+    pub fn distinct_pictures(&mut self) -> usize {
+        self.reset();
+        let mut pictures: Vec<Board<Cell>> = vec![];
+        for solution in self.get_solutions_capped(DISTINCT_PICTURES_CAP) {
+            if !pictures
+                .iter()
+                .any(|picture| board::equivalent_under_symmetry(picture, &solution))
+            {
+                pictures.push(solution);
+            }
+        }
+        pictures.len()
+    }
+    /// Restarts the search and checks whether the puzzle has exactly one solution.
+    pub fn is_unique(&mut self) -> bool {
+        self.reset();
+        self.get_solutions_capped(2).len() == 1
+    }
+    /// Restarts the search and tells apart puzzles with no solution, one solution and several,
+    /// giving back the solution when it is unique.
+    pub fn solution_cardinality(&mut self) -> Cardinality {
+        self.reset();
+        let mut solutions = self.get_solutions_capped(2);
+        match (solutions.pop(), solutions.is_empty()) {
+            (None, _) => Cardinality::None,
+            (Some(solution), true) => Cardinality::Unique(solution),
+            (Some(_), false) => Cardinality::Multiple,
+        }
+    }
+    /// Restarts the search and finds the cells that have the same value in every solution. Cells
+    /// where solutions disagree, or every cell if there is no solution, are `None`.
+    ///
+    /// At most [`FORCED_CELLS_CAP`] solutions are compared, so for extremely ambiguous puzzles
+    /// a cell may be reported as forced even though an uncounted solution disagrees.
+    pub fn forced_cells(&mut self) -> GuessBoard {
+        self.reset();
+        let solutions = self.get_solutions_capped(FORCED_CELLS_CAP);
+        let mut forced = Board::new_default(self.width(), self.height());
+        if let Some((first, rest)) = solutions.split_first() {
+            for (i, (cell, &value)) in forced
+                .as_slice_mut()
+                .iter_mut()
+                .zip(first.as_slice())
+                .enumerate()
+            {
+                *cell = rest
+                    .iter()
+                    .all(|s| s.as_slice()[i] == value)
+                    .then_some(value);
+            }
+        }
+        forced
+    }
+    /// How many guesses the search is currently nested inside, i.e. the number of alternatives
+    /// waiting to be tried.
+    pub const fn current_depth(&self) -> usize {
+        // Every snapshot has a guess, including those spilled to disk.
+        self.guesses.len()
+    }
+    /// How many cells were determined by the row passes and by the column passes, counted
+    /// across every branch of the search since the last reset.
+    pub const fn deduction_source_counts(&self) -> (usize, usize) {
+        (self.row_deductions, self.col_deductions)
+    }
+    /// Runs line logic without guessing until it gets stuck or finds a line with no valid
+    /// arrangement, and explains the latter. Contradictions that only appear after guessing are
+    /// not found.
+    pub fn explain_contradiction(&mut self) -> Option<Explanation> {
+        let mut first_run = true;
+        loop {
+            match self.propagate(first_run) {
+                Some(true) => first_run = false,
+                Some(false) => return None,
+                None => break,
             }
+        }
+        let (axis, index) = self.contradiction?;
+        let (line, perms) = match axis {
+            Axis::Row => (
+                self.board.row(index).to_vec(),
+                &self.fresh_rows_perms[index],
+            ),
+            Axis::Col => (self.board.col(index), &self.fresh_cols_perms[index]),
+        };
+        let mut positions: Vec<usize> = perms
+            .iter()
+            .filter_map(|perm| hint::first_mismatch(perm, &line))
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let cells = positions
+            .into_iter()
+            .map(|i| (i, line[i].unwrap()))
+            .collect();
+        Some(Explanation { axis, index, cells })
+    }
+    /// Whether line logic alone, without guessing, fixes the cell at `(x, y)`, and if so its
+    /// value. Returns `None` if the cell is still ambiguous or the puzzle is contradictory.
+    ///
+    /// The deductions made along the way are kept on the board.
+    pub fn cell_is_forced(&mut self, x: usize, y: usize) -> Option<Cell> {
+        assert!(
+            x < self.width() && y < self.height(),
+            "cell ({}, {}) out of bounds",
+            x,
+            y
+        );
+        let mut first_run = true;
+        loop {
+            match self.propagate(first_run)? {
+                true => first_run = false,
+                false => return self.board.row(y)[x],
+            }
+        }
+    }
+    /// Skips `n` solutions and returns the one after them, or `None` if there are no more than
+    /// `n` remaining.
+    pub fn nth_solution(&mut self, n: usize) -> Option<Board<Cell>> {
+        for _ in 0..n {
+            self.find_solution()?;
+        }
+        self.find_solution()
+    }
+    /// The cells the search has guessed are filled on its current branch, outermost guess first.
+    /// Line logic hasn't confirmed them, and a later contradiction may still undo them.
+    pub fn guessed_cells(&self) -> &[(usize, usize)] {
+        &self.guesses
+    }
+    /// Keeps at most about `bytes` of backtrack snapshots in memory, moving older ones to a
+    /// temporary file that is deleted when the solver is dropped. The newest snapshot always
+    /// stays in memory, so `0` spills everything else. Snapshots only move when the search
+    /// guesses, so lowering the threshold takes effect at the next guess.
+    ///
+    /// # Panics
+    ///
+    /// The search panics if the spill file can't be created, written or read.
+    #[cfg(feature = "spill")]
+    pub fn set_spill_threshold(&mut self, bytes: usize) {
+        self.spill.set_threshold(Some(bytes));
+    }
+    /// Starts recording a [`SolveEvent`] for every step the solver takes. This has a cost on
+    /// every pass, so it is off by default.
+    pub fn enable_logging(&mut self) {
+        self.log.get_or_insert_with(Vec::new);
+    }
+    /// The events recorded since logging was enabled, or an empty slice if it never was.
+    pub fn log(&self) -> &[SolveEvent] {
+        self.log.as_deref().unwrap_or_default()
+    }
+    fn pop_backtrack(&mut self) -> Option<()> {
+        #[cfg(not(feature = "spill"))]
+        let popped = self.backtrack.pop();
+        #[cfg(feature = "spill")]
+        let popped = self
+            .spill
+            .pop(&mut self.backtrack)
+            .expect("failed to read from the backtrack spill file");
+        let Some((board, rows_perms, cols_perms)) = popped else {
+            self.exhausted = true;
+            return None;
+        };
+        self.guesses.pop();
+        let _abandoned = (
+            mem::replace(&mut self.board, board),
+            mem::replace(&mut self.rows_perms, rows_perms),
+            mem::replace(&mut self.cols_perms, cols_perms),
+        );
+        #[cfg(feature = "pool")]
+        self.pool.push(_abandoned);
+        self.mark_all_dirty();
+        event::record(&mut self.log, SolveEvent::Backtracked);
+        Some(())
+    }
+    /// Copies the current state, into pooled buffers when the `pool` feature has some spare.
+    fn snapshot(&mut self) -> Snapshot {
+        #[cfg(feature = "pool")]
+        if let Some(mut spare) = self.pool.pop() {
+            spare.0.clone_from(&self.board);
+            spare.1.clone_from(&self.rows_perms);
+            spare.2.clone_from(&self.cols_perms);
+            return spare;
+        }
+        (
+            self.board.clone(),
+            self.rows_perms.clone(),
+            self.cols_perms.clone(),
+        )
+    }
+    /// Applies one row and column pass of line logic to the board without guessing, and returns
+    /// whether any cell was determined. `false` means the puzzle is solved, stuck, or
+    /// contradictory.
+    pub fn step_once(&mut self) -> bool {
+        self.propagate(true).unwrap_or(false)
+    }
+    /// Finds the next solution, continuing the search where the previous call left off.
+    ///
+    /// Calling this until it returns `None` visits every solution exactly once. After that,
+    /// every call returns `None` straight away until [`Picross::reset`] restarts the search.
+    pub fn find_solution(&mut self) -> Option<Board<Cell>> {
+        self.find_solution_with(|_| {})
+    }
+    /// Like [`Picross::find_solution`], but calls `on_pass` with the solver state after every
+    /// full row and column propagation pass.
+    pub fn find_solution_with<F>(&mut self, mut on_pass: F) -> Option<Board<Cell>>
+    where
+        F: FnMut(&Self),
+    {
+        // The callback never breaks, so the search is never interrupted.
+        self.search(|picross| {
+            on_pass(picross);
+            ControlFlow::Continue(())
+        })
+        .ok()
+        .flatten()
+    }
+    /// Like [`Picross::find_solution`], but writes every step of the search to `out` as it
+    /// happens: the deductions of each pass, each guess, and each backtrack.
+    pub fn solve_verbose(&mut self, out: &mut impl Write) -> io::Result<Option<Board<Cell>>> {
+        let was_logging = self.log.is_some();
+        self.enable_logging();
+        let mut written = self.log().len();
+        let mut result = Ok(());
+        let solution = self.find_solution_with(|picross| {
+            if result.is_ok() {
+                result = write_events(out, &picross.log()[written..])
+                    .and_then(|()| writeln!(out, "-- end of pass {}", picross.num_passes));
+            }
+            written = picross.log().len();
+        });
+        if result.is_ok() {
+            result = write_events(out, &self.log()[written..]);
+        }
+        if !was_logging {
+            self.log = None;
+        }
+        result.map(|()| solution)
+    }
+    /// The search loop behind [`Picross::find_solution_with`]. If `on_pass` breaks, the search
+    /// stops early and can be resumed by a later call.
+    fn search<F>(&mut self, mut on_pass: F) -> Result<Option<Board<Cell>>, Interrupted>
+    where
+        F: FnMut(&Self) -> ControlFlow<()>,
+    {
+        if self.exhausted {
+            return Ok(None);
+        }
+        let mut first_run = true;
+        // A fresh search first fills the cells each line's hint forces on its own, which is
+        // cheap, and counts them towards the first pass's progress.
+        let mut edge_progress = self.num_passes == 0 && self.fill_edge_forced();
+        loop {
+            let propagated = self
+                .propagate(first_run)
+                .filter(|_| !self.require_connected || self.board.filled_could_connect());
+            let progressed = match propagated {
+                Some(progressed) => progressed || mem::take(&mut edge_progress),
+                None => {
+                    if self.pop_backtrack().is_none() {
+                        return Ok(None);
+                    }
+                    continue;
+                }
+            };
+            let stop = on_pass(self).is_break();
+            let resumed = first_run;
             first_run = false;
             if progressed {
-                if self.board.as_slice().iter().all(Option::is_some) {
-                    // Found a solution
-                    let (w, h) = (self.width(), self.height());
-                    let mut finished_board = Board::new_default(w, h);
-                    for y in 0..h {
-                        let finished_row = self
-                            .board
-                            .row(y)
-                            .iter()
-                            .copied()
-                            .map(Option::unwrap)
-                            .collect();
-                        finished_board.set_row(y, finished_row);
+                if let Some(finished_board) = self.solved_board() {
+                    // The second axis of a pass can fill cells the first axis never checked, so
+                    // a full board isn't necessarily a solution.
+                    if !self.lines_consistent() {
+                        if self.pop_backtrack().is_none() {
+                            return Ok(None);
+                        }
+                        continue;
+                    }
+                    #[cfg(debug_assertions)]
+                    if let Err((axis, index)) = self.check_solution(&finished_board) {
+                        panic!(
+                            "solver produced a board whose {} {} doesn't match its hint:\n{}",
+                            axis, index, finished_board
+                        );
                     }
-                    return Some(finished_board);
+                    // Found a solution. This takes priority over stopping, since a resumed
+                    // search would skip past a board that is already full.
+                    return Ok(Some(finished_board));
                 }
-            } else {
-                // Solver got stuck, do bifurcation
-                // TODO: Does this code only execute if there are multiple solutions?
+            }
+            if stop {
+                return Err(Interrupted);
+            }
+            if !progressed {
+                // Solver got stuck, do bifurcation. This isn't limited to puzzles with several
+                // solutions: line logic alone can't solve every uniquely solvable puzzle.
                 // First, find unsolved cell
-                let i = self
+                let cell = self
                     .board
-                    .as_slice()
-                    .iter()
-                    .enumerate()
-                    .find_map(|(i, v)| v.is_none().then_some(i));
-                match i {
-                    Some(i) => {
+                    .iter_positions()
+                    .find_map(|(x, y, v)| v.is_none().then_some((x, y)));
+                match cell {
+                    Some(cell @ (x, y)) => {
                         // Found an unsolved cell, branch into two different boards where that cell is filled or unfilled.
-                        let mut alternate = self.board.clone();
-                        self.board.as_slice_mut()[i] = Some(true);
-                        alternate.as_slice_mut()[i] = Some(false);
-                        self.backtrack.push((
-                            alternate,
-                            self.rows_perms.clone(),
-                            self.cols_perms.clone(),
-                        ));
+                        let mut alternate = self.snapshot();
+                        *self.board.pos_mut(x, y) = Some(true);
+                        *alternate.0.pos_mut(x, y) = Some(false);
+                        (self.dirty_rows[y], self.dirty_cols[x]) = (true, true);
+                        #[cfg(not(feature = "spill"))]
+                        self.backtrack.push(alternate);
+                        #[cfg(feature = "spill")]
+                        self.spill
+                            .push(&mut self.backtrack, alternate)
+                            .expect("failed to write to the backtrack spill file");
+                        self.guesses.push(cell);
                         self.num_backtracks += 1;
-                        //println!("uwu {} -> {}", self.num_backtracks, self.backtrack.len());
+                        event::record(&mut self.log, SolveEvent::Bifurcated { cell });
                     }
                     None => {
-                        // If all cells are solved, attempt to backtrack.
-                        (self.board, self.rows_perms, self.cols_perms) = self.backtrack.pop()?;
-                        //println!("owo");
+                        // All cells are solved. The pass that fills the last cell always makes
+                        // progress and returns the board, so this is a board that was already
+                        // returned by the previous call. Move on to the next alternative.
+                        debug_assert!(resumed, "a full board was reached but never returned");
+                        if self.pop_backtrack().is_none() {
+                            return Ok(None);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    /// Whether every line of the board is still one of that line's remaining arrangements.
+    fn lines_consistent(&self) -> bool {
+        let contains = |perms: &[Vec<Cell>], line: &[Option<Cell>]| {
+            perms.iter().any(|perm| hint::perm_matches(perm, line))
+        };
+        (0..self.height()).all(|y| contains(&self.rows_perms[y], self.board.row(y)))
+            && (0..self.width()).all(|x| contains(&self.cols_perms[x], &self.board.col(x)))
+    }
+    /// Fills the unknown cells that [`Hint::edge_forced`] decides for every row and column, and
+    /// returns whether there were any.
+    fn fill_edge_forced(&mut self) -> bool {
+        let mut progressed = false;
+        for axis in [Axis::Row, Axis::Col] {
+            let (hints, len) = match axis {
+                Axis::Row => (&self.row_hints, self.width()),
+                Axis::Col => (&self.col_hints, self.height()),
+            };
+            for (index, lines) in hints.iter().enumerate() {
+                let mut cells = 0;
+                for (i, forced) in Hint::new(lines).edge_forced(len).into_iter().enumerate() {
+                    let (x, y) = match axis {
+                        Axis::Row => (i, index),
+                        Axis::Col => (index, i),
+                    };
+                    let cell = self.board.pos_mut(x, y);
+                    if cell.is_none() && forced.is_some() {
+                        *cell = forced;
+                        cells += 1;
+                    }
+                }
+                if cells > 0 {
+                    progressed = true;
+                    match axis {
+                        Axis::Row => self.row_deductions += cells,
+                        Axis::Col => self.col_deductions += cells,
                     }
+                    let event = SolveEvent::Deduced { axis, index, cells };
+                    event::record(&mut self.log, event);
+                }
+            }
+        }
+        progressed
+    }
+    /// Runs one pass of line solving over every row and every column, in the order set by
+    /// [`Picross::set_axis_order`]. Returns whether any cell was determined, or `None` if some
+    /// line has no arrangement left.
+    fn propagate(&mut self, first_run: bool) -> Option<bool> {
+        let cols_first = match self.axis_order {
+            AxisOrder::RowsFirst => false,
+            AxisOrder::ColsFirst => true,
+            AxisOrder::Alternate => self.num_passes % 2 == 1,
+        };
+        let axes = if cols_first {
+            [Axis::Col, Axis::Row]
+        } else {
+            [Axis::Row, Axis::Col]
+        };
+        let mut progressed = false;
+        for axis in axes {
+            progressed |= match axis {
+                Axis::Row => self.propagate_rows(first_run)?,
+                Axis::Col => self.propagate_cols(first_run)?,
+            };
+        }
+        self.num_passes += 1;
+        Some(progressed)
+    }
+    fn mark_all_dirty(&mut self) {
+        self.dirty_rows.fill(true);
+        self.dirty_cols.fill(true);
+    }
+    fn propagate_rows(&mut self, first_run: bool) -> Option<bool> {
+        let mut progressed = false;
+        for (y, row_perms) in self.rows_perms.iter_mut().enumerate() {
+            let dirty = mem::replace(&mut self.dirty_rows[y], false);
+            if !dirty && !first_run {
+                continue;
+            }
+            if row_perms.len() == 1 && !first_run {
+                // The pass that narrowed this line to one arrangement already wrote it to the
+                // board, and fully known cells can't be changed by the other axis.
+                continue;
+            }
+            let row = self.board.row(y);
+            let old_len = row_perms.len();
+            row_perms.retain(|perm| hint::perm_matches(perm, row));
+            let removed = old_len - row_perms.len();
+            if removed > 0 {
+                event::record(&mut self.log, SolveEvent::FilteredRow { y, removed });
+            }
+            if row_perms.len() < old_len || first_run {
+                let Some(new_row) = sum_line(row_perms) else {
+                    self.contradiction = Some((Axis::Row, y));
+                    return None;
+                };
+                if new_row != row {
+                    progressed = true;
+                    let cells = count_changes(row, &new_row);
+                    mark_changes(&mut self.dirty_cols, row, &new_row);
+                    self.row_deductions += cells;
+                    event::record(
+                        &mut self.log,
+                        SolveEvent::Deduced {
+                            axis: Axis::Row,
+                            index: y,
+                            cells,
+                        },
+                    );
+                    self.board.set_row(y, new_row);
+                }
+            }
+        }
+        Some(progressed)
+    }
+    fn propagate_cols(&mut self, first_run: bool) -> Option<bool> {
+        let mut progressed = false;
+        for (x, col_perms) in self.cols_perms.iter_mut().enumerate() {
+            let dirty = mem::replace(&mut self.dirty_cols[x], false);
+            if !dirty && !first_run {
+                continue;
+            }
+            if col_perms.len() == 1 && !first_run {
+                continue;
+            }
+            let col = self.board.col(x);
+            let old_len = col_perms.len();
+            col_perms.retain(|perm| hint::perm_matches(perm, &col));
+            let removed = old_len - col_perms.len();
+            if removed > 0 {
+                event::record(&mut self.log, SolveEvent::FilteredCol { x, removed });
+            }
+            if col_perms.len() < old_len || first_run {
+                let Some(new_col) = sum_line(col_perms) else {
+                    self.contradiction = Some((Axis::Col, x));
+                    return None;
+                };
+                if new_col != col {
+                    progressed = true;
+                    let cells = count_changes(&col, &new_col);
+                    mark_changes(&mut self.dirty_rows, &col, &new_col);
+                    self.col_deductions += cells;
+                    event::record(
+                        &mut self.log,
+                        SolveEvent::Deduced {
+                            axis: Axis::Col,
+                            index: x,
+                            cells,
+                        },
+                    );
+                    self.board.set_col(x, new_col);
                 }
             }
         }
+        Some(progressed)
+    }
+    /// The board as it stands, if every cell is determined. After [`Picross::find_solution`]
+    /// returns a solution this is that solution, until the search continues.
+    pub fn solved_board(&self) -> Option<Board<Cell>> {
+        if !self.board.as_slice().iter().all(Option::is_some) {
+            return None;
+        }
+        let (w, h) = (self.width(), self.height());
+        let mut finished_board = Board::new_default(w, h);
+        for y in 0..h {
+            let finished_row = self
+                .board
+                .row(y)
+                .iter()
+                .copied()
+                .map(Option::unwrap)
+                .collect();
+            finished_board.set_row(y, finished_row);
+        }
+        Some(finished_board)
+    }
+    /// Solves the puzzle by line logic alone, failing instead of guessing when that isn't
+    /// enough.
+    pub fn solve_strict(&mut self) -> Result<Board<Cell>, StrictError> {
+        let mut first_run = true;
+        loop {
+            let progressed = self
+                .propagate(first_run)
+                .ok_or(StrictError::Contradiction)?;
+            first_run = false;
+            // Only trust a full board once a pass confirms it; the column pass can fill the last
+            // cells with values the rows haven't checked yet.
+            if !progressed {
+                return self.solved_board().ok_or(StrictError::RequiresGuessing);
+            }
+        }
+    }
+    /// Applies line logic without guessing until [`Picross::progress`] reaches `frac`, and returns
+    /// the partly solved board. Progress is checked between passes, so a pass can overshoot
+    /// `frac`. If line logic gets stuck or hits a contradiction first, this returns what it had
+    /// determined by then.
+    pub fn solve_until_fraction(&mut self, frac: f32) -> GuessBoard {
+        let mut first_run = true;
+        while self.progress() < frac && self.propagate(first_run) == Some(true) {
+            first_run = false;
+        }
+        self.board.clone()
+    }
+}
+
+/// Whether two puzzles have exactly the same set of solutions, e.g. to check that converting a
+/// puzzle between formats preserved it. Neither puzzle's search state is affected.
+///
+/// This enumerates every solution, so it is only practical for puzzles with few of them. Puzzles
+/// with more than [`EQUIVALENCE_CAP`] solutions are never considered equivalent.
+fn puzzles_equivalent(a: &Picross, b: &Picross) -> bool {
+    let solutions = |picross: &Picross| {
+        let solutions = picross
+            .clone_fresh()
+            .get_solutions_capped(EQUIVALENCE_CAP + 1);
+        (solutions.len() <= EQUIVALENCE_CAP).then(|| solutions.into_iter().collect::<HashSet<_>>())
+    };
+    match (solutions(a), solutions(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// The width in columns of the terminal on stdout. Falls back to the `COLUMNS` environment
+/// variable when stdout isn't a terminal that can be asked, then to 80.
+#[cfg(feature = "std")]
+fn terminal_width() -> usize {
+    queried_terminal_width()
+        .or_else(|| {
+            std::env::var("COLUMNS")
+                .ok()
+                .and_then(|columns| columns.trim().parse().ok())
+                .filter(|&columns| columns > 0)
+        })
+        .unwrap_or(80)
+}
+
+#[cfg(all(feature = "std", unix))]
+fn queried_terminal_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: `TIOCGWINSZ` only writes a `winsize` through the pointer, which points to one.
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    // Some ptys and serial consoles answer, but with a width of 0.
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn queried_terminal_width() -> Option<usize> {
+    None
+}
+
+fn write_events(out: &mut impl Write, events: &[SolveEvent]) -> io::Result<()> {
+    events
+        .iter()
+        .try_for_each(|event| writeln!(out, "{}", event))
+}
+
+/// The cells every arrangement of a line agrees on, or `None` if there are no arrangements.
+fn sum_line(perms: &[Vec<Cell>]) -> Option<Vec<Option<Cell>>> {
+    match perms {
+        // Nothing to compare a lone arrangement against.
+        [only] => Some(only.iter().copied().map(Some).collect()),
+        _ => hint::sum_perms(perms.iter().cloned()),
+    }
+}
+
+/// Whether every hint fits in a line of `len` cells. Hints too long to add up never fit.
+fn hints_fit(hints: &[Hint<'_>], len: usize) -> bool {
+    hints.iter().all(|hint| hint.min_length() <= len)
+}
+
+fn count_changes(old: &[Option<Cell>], new: &[Option<Cell>]) -> usize {
+    old.iter().zip(new).filter(|(a, b)| a != b).count()
+}
+
+/// Marks the crossing lines of every cell that differs between `old` and `new` as dirty.
+fn mark_changes(dirty: &mut [bool], old: &[Option<Cell>], new: &[Option<Cell>]) {
+    for ((flag, a), b) in dirty.iter_mut().zip(old).zip(new) {
+        *flag |= a != b;
     }
 }
 
@@ -188,18 +1308,68 @@ impl fmt::Display for Picross {
     }
 }
 
-fn make_hints(s: &str) -> Option<HintHolder> {
-    s.split(',')
-        .map(|h| {
+/// Parses comma-separated lines of whitespace-separated run lengths. A token of the form `NxM`
+/// is shorthand for `M` runs of length `N`.
+///
+/// Every comma separates two lines, so a segment with no runs (including the empty string, or
+/// the text after a trailing comma) is a blank line with no filled cells.
+///
+/// Runs are separated by any Unicode whitespace, which includes tabs, line breaks and
+/// non-breaking spaces, and whitespace around runs is ignored.
+fn make_hints(s: &str) -> Result<HintHolder<'_>, HintParseError> {
+    make_hints_with(s, false)
+}
+
+/// Like [`make_hints`], but when `strict` is set a trailing comma (one followed only by
+/// whitespace) is reported as [`HintParseError::TrailingComma`] instead of adding a blank line.
+fn make_hints_with(s: &str, strict: bool) -> Result<HintHolder<'_>, HintParseError> {
+    if strict {
+        if let Some((_, last)) = s.rsplit_once(',') {
+            if last.trim().is_empty() {
+                return Err(HintParseError::TrailingComma);
+            }
+        }
+    }
+    make_hints_from_lines(s.split(','))
+}
+
+/// Like [`make_hints`], but every clue is a run length followed by a color letter from
+/// `palette`, as in `3b 2r, 1b`.
+fn make_color_hints<C: Copy>(
+    s: &str,
+    palette: &Palette<C>,
+) -> Result<ColorHintHolder<C>, HintParseError> {
+    let lines = s
+        .split(',')
+        .enumerate()
+        .map(|(line, h)| {
             h.split_whitespace()
-                //.filter(|s| !s.is_empty())
-                .map(|n| n.parse().ok().and_then(NonZeroUsize::new))
+                .map(|token| parse::parse_color_run(line, token, palette))
                 .collect()
         })
-        .collect::<Option<_>>()
-        .map(HintHolder::new)
+        .collect::<Result<_, _>>()?;
+    Ok(ColorHintHolder { lines })
 }
 
+/// Parses hints that are already split into lines, each holding whitespace-separated runs.
+fn make_hints_from_lines<'a, I, S>(lines: I) -> Result<HintHolder<'a>, HintParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    lines
+        .into_iter()
+        .map(|line| {
+            line.as_ref()
+                .split_whitespace()
+                .map(parse::parse_runs)
+                .collect::<Result<Vec<_>, _>>()
+                .map(|runs| runs.concat())
+        })
+        .collect()
+}
+
+#[cfg(not(fuzzing))]
 fn main() {
     // std::env::set_var("RUST_BACKTRACE", "1");
     // std::env::set_var("RUST_BACKTRACE", "full");
@@ -242,14 +1412,33 @@ fn main() {
     //assert_eq!(col_hints.get().len(), 20);
     let mut b = Picross::new(row_hints.get(), col_hints.get());
     let mut successful = false;
+    // `--fps N` animates the solve instead of stepping on Enter.
+    #[cfg(feature = "tui")]
+    let fps = std::env::args()
+        .skip_while(|arg| arg != "--fps")
+        .nth(1)
+        .and_then(|n| n.parse().ok());
     println!("Running...");
     loop {
+        #[cfg(feature = "std")]
         let start = Instant::now();
+        #[cfg(feature = "tui")]
+        let bs = tui::watch(&mut b, fps);
+        #[cfg(not(feature = "tui"))]
         let bs = b.find_solution();
+        #[cfg(feature = "std")]
         let time = start.elapsed();
         let uwu = bs.is_none();
         match bs {
             Some(solved) => {
+                #[cfg(feature = "std")]
+                let solved = match solved.fit_width(terminal_width()) {
+                    (1, _) => solved,
+                    (factor, scaled) => {
+                        println!("Scaled down 1:{} to fit the terminal.", factor);
+                        scaled
+                    }
+                };
                 println!("Found solution:\n{}", solved);
                 if b.num_backtracks > 0 {
                     println!("Required {} backtracks.", b.num_backtracks);
@@ -271,6 +1460,7 @@ fn main() {
                 }
             }
         }
+        #[cfg(feature = "std")]
         println!("Time taken: {}μs", time.as_micros());
         if uwu {
             break;
@@ -278,3 +1468,846 @@ fn main() {
     }
     //assert!(bs.is_some());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use board::assert_board_eq;
+
+    #[test]
+    fn hint_shorthand() {
+        let hints = make_hints("1 2x3, 4x1 1").unwrap();
+        let lines: Vec<_> = hints.get().iter().map(|h| h.lines().to_vec()).collect();
+        let u = |x| NonZeroUsize::new(x).unwrap();
+        assert_eq!(lines, [vec![u(1), u(2), u(2), u(2)], vec![u(4), u(1)]]);
+        assert_eq!(make_hints("1x4096").unwrap().get()[0].len(), 4096);
+        for bad in [
+            "3x",
+            "0x2",
+            "2x0",
+            "x2",
+            "2x3x4",
+            "1x4097",
+            "1x100000000000",
+        ] {
+            assert_eq!(
+                make_hints(bad).err(),
+                Some(HintParseError::InvalidShorthand(bad.to_string()))
+            );
+        }
+        assert_eq!(
+            make_hints("1 0").err(),
+            Some(HintParseError::InvalidRun("0".to_string()))
+        );
+    }
+
+    #[test]
+    fn canonical_hints() {
+        let tidy = make_hints("2 1, 1x2").unwrap();
+        let messy = make_hints(" 2   1 ,1  1").unwrap();
+        assert_eq!(tidy.canonical(), messy.canonical());
+
+        let cols = make_hints("2, 1").unwrap();
+        let from_text = puzzle::Puzzle::new(messy.canonical(), cols.canonical());
+        let from_cwd = &parse::parse_cwd("2\n2\n2 1\n1 1\n2\n1\n").unwrap()[0];
+        assert_eq!(from_text.fingerprint(), from_cwd.fingerprint());
+        let other = puzzle::Puzzle::new(cols.canonical(), messy.canonical());
+        assert_ne!(from_text.fingerprint(), other.fingerprint());
+    }
+
+    #[test]
+    fn single_hint() {
+        let hints = make_hints("1 2, 3").unwrap();
+        assert_eq!(hints.hint(1).map(Hint::total), Some(3));
+        assert_eq!(hints.hint(0).map(Hint::len), Some(2));
+        assert!(hints.hint(2).is_none());
+    }
+
+    #[test]
+    fn collect_hints() {
+        let u = |x| NonZeroUsize::new(x).unwrap();
+        let holder: HintHolder = (1..=3).map(|n| vec![u(n); n]).collect();
+        let lines: Vec<_> = holder.get().iter().map(|h| h.lines().to_vec()).collect();
+        assert_eq!(lines, [vec![u(1)], vec![u(2); 2], vec![u(3); 3]]);
+        assert_eq!(
+            holder.canonical(),
+            make_hints("1, 2 2, 3x3").unwrap().canonical()
+        );
+    }
+
+    #[test]
+    fn blank_hint_lines() {
+        let lens =
+            |s| -> Vec<usize> { make_hints(s).unwrap().get().iter().map(Hint::len).collect() };
+        assert_eq!(lens("2 1,,3"), [2, 0, 1]);
+        assert_eq!(lens("2 1, ,3"), [2, 0, 1]);
+        assert_eq!(lens("1, 2,"), [1, 1, 0]);
+        assert_eq!(lens(",1"), [0, 1]);
+        assert_eq!(lens(""), [0]);
+    }
+
+    #[test]
+    fn capped_solutions() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.get_solutions_capped(1).len(), 1);
+        assert_eq!(b.get_solutions_capped(5).len(), 1);
+        assert!(b.get_solutions_capped(5).is_empty());
+    }
+
+    #[test]
+    fn display_solution() {
+        let row_hints = make_hints("2, 1, 1 1").unwrap();
+        let col_hints = make_hints("3, 1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let solved = b.find_solution().unwrap();
+        assert_eq!(solved.to_string(), "XX.\nX..\nX.X");
+    }
+
+    #[test]
+    fn nth_solution() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let solutions = b.get_solutions();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_board_eq!(b.nth_solution(1).unwrap(), solutions[1]);
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert!(b.nth_solution(2).is_none());
+    }
+
+    #[test]
+    fn update_hints() {
+        let row_hints = make_hints("2,").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.find_solution().unwrap().to_string(), "XX\n..");
+        let edited = make_hints(",2").unwrap();
+        b.update_row_hint(0, &edited.get()[0]);
+        b.update_row_hint(1, &edited.get()[1]);
+        assert_eq!(b.get_solutions_capped(2).len(), 1);
+        b.reset();
+        assert_eq!(b.find_solution().unwrap().to_string(), "..\nXX");
+    }
+
+    #[test]
+    fn forced() {
+        // Two solutions that differ in the right two columns of the top and bottom rows.
+        let row_hints = make_hints("1, 3, 1").unwrap();
+        let col_hints = make_hints("1, 2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.get_solutions().len(), 2);
+        assert_eq!(b.forced_cells().to_string(), ".??\nXXX\n.??");
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.forced_cells().to_string(), ".X.\nXXX\n.X.");
+    }
+
+    #[test]
+    fn strict() {
+        let row_hints = make_hints("2, 1, 1 1").unwrap();
+        let col_hints = make_hints("3, 1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.solve_strict().unwrap().to_string(), "XX.\nX..\nX.X");
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.solve_strict(), Err(StrictError::RequiresGuessing));
+        let col_hints = make_hints("2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.solve_strict(), Err(StrictError::Contradiction));
+    }
+
+    #[test]
+    fn deduction_sources() {
+        let row_hints = make_hints("2, 1, 1 1").unwrap();
+        let col_hints = make_hints("3, 1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        b.find_solution();
+        let (rows, cols) = b.deduction_source_counts();
+        assert_eq!(rows + cols, 9);
+        // Before any pass, the rows `2` and `1 1` fix four cells and the column `3` two more.
+        // The first row pass then finishes it.
+        assert_eq!((rows, cols), (7, 2));
+    }
+
+    #[test]
+    fn axis_order() {
+        // A wide, flat puzzle where the short columns pin down everything at once.
+        let row_hints = make_hints("1, 3, 1 1 1").unwrap();
+        let col_hints = make_hints("1, 1, 2, 2, 1").unwrap();
+        let passes = |order| {
+            let mut b = Picross::new(row_hints.get(), col_hints.get());
+            b.set_axis_order(order);
+            assert_eq!(
+                b.find_solution().unwrap().to_string(),
+                "...X.\n.XXX.\nX.X.X"
+            );
+            b.num_passes
+        };
+        assert_eq!(passes(AxisOrder::RowsFirst), 2);
+        assert_eq!(passes(AxisOrder::ColsFirst), 1);
+        assert_eq!(passes(AxisOrder::Alternate), 2);
+    }
+
+    #[test]
+    fn prefilled() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let (rows, cols) = (row_hints.get(), row_hints.get());
+        let solved = Picross::solve_from_picture(rows, cols, &[(1, 0, true)]);
+        assert_eq!(solved.unwrap().unwrap().to_string(), ".X\nX.");
+        let solved = Picross::solve_from_picture(rows, cols, &[(1, 0, false)]);
+        assert_eq!(solved.unwrap().unwrap().to_string(), "X.\n.X");
+        assert_eq!(
+            Picross::solve_from_picture(rows, cols, &[(2, 0, true)]),
+            Err(PrefillError::OutOfBounds { x: 2, y: 0 })
+        );
+        assert_eq!(
+            Picross::solve_from_picture(rows, cols, &[(0, 0, true), (0, 0, false)]),
+            Err(PrefillError::Conflict { x: 0, y: 0 })
+        );
+        assert_eq!(
+            Picross::solve_from_picture(rows, cols, &[(0, 0, true), (1, 0, true)]),
+            Err(PrefillError::Contradiction {
+                axis: Axis::Row,
+                index: 0
+            })
+        );
+    }
+
+    #[test]
+    fn permutation_access() {
+        let row_hints = make_hints("1, 2").unwrap();
+        let col_hints = make_hints("1, 1, 1").unwrap();
+        let b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.row_permutations(0).len(), 3);
+        assert_eq!(
+            b.row_permutations(1),
+            [[true, true, false], [false, true, true]]
+        );
+        assert_eq!(b.col_permutations(2).len(), 2);
+        assert!(b.row_permutations(2).is_empty());
+        assert!(b.col_permutations(3).is_empty());
+    }
+
+    #[test]
+    fn progress() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.progress(), 0.0);
+        b.find_solution().unwrap();
+        assert_eq!(b.progress(), 1.0);
+        assert_eq!(Picross::new(&[], &[]).progress(), 1.0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bounded_solutions() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let (solutions, truncated) = b.get_solutions_bounded(1, Duration::from_secs(60));
+        assert_eq!(solutions.len(), 1);
+        assert!(truncated);
+        b.reset();
+        let (solutions, truncated) = b.get_solutions_bounded(5, Duration::MAX);
+        assert_eq!(solutions.len(), 2);
+        assert!(!truncated);
+        b.reset();
+        let (solutions, truncated) = b.get_solutions_bounded(5, Duration::ZERO);
+        assert!(solutions.is_empty());
+        assert!(truncated);
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let row_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let col_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let solve = || Picross::new(row_hints.get(), col_hints.get()).get_solutions();
+        let first = solve();
+        assert!(first.len() > 1);
+        for _ in 0..5 {
+            assert_eq!(solve(), first);
+        }
+    }
+
+    #[test]
+    fn single_step() {
+        let row_hints = make_hints("3, 1, 1").unwrap();
+        let col_hints = make_hints("1, 3, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert!(b.step_once());
+        assert_eq!(b.progress(), 1.0);
+        assert_eq!(b.to_string(), "XXX\n.X.\n.X.");
+        assert!(!b.step_once());
+
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert!(!b.step_once());
+        assert_eq!(b.progress(), 0.0);
+    }
+
+    #[test]
+    fn duplicate_lines() {
+        let row_hints = make_hints("1 1, 1 1, 3, 1 1").unwrap();
+        let col_hints = make_hints("1, 1 1, 1, 3").unwrap();
+        let b = Picross::new(row_hints.get(), col_hints.get());
+        let expected = row_hints.get()[0].permutations(4);
+        assert_eq!(b.row_permutations(0), expected);
+        assert_eq!(b.row_permutations(1), expected);
+        assert_eq!(b.row_permutations(3), expected);
+        assert_eq!(b.row_permutations(2).len(), 2);
+        assert_eq!(b.col_permutations(0), b.col_permutations(2));
+    }
+
+    #[test]
+    fn contradiction_explanation() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let explanation = b.explain_contradiction().unwrap();
+        assert_eq!(explanation.axis, Axis::Row);
+        assert_eq!(explanation.index, 0);
+        assert_eq!(explanation.cells, [(0, true), (1, true)]);
+
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.explain_contradiction(), None);
+    }
+
+    #[cfg(feature = "pool")]
+    #[test]
+    fn pooled_snapshots() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.get_solutions().len(), 2);
+        assert!(!b.pool.is_empty());
+        let spare = b.pool.last().unwrap().0.as_slice().as_ptr();
+        b.reset();
+        b.find_solution();
+        assert_eq!(b.backtrack[0].0.as_slice().as_ptr(), spare);
+    }
+
+    #[test]
+    fn event_log() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        b.find_solution();
+        assert!(b.log().is_empty());
+
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        b.enable_logging();
+        b.find_solution();
+        assert_eq!(b.log()[0], SolveEvent::Bifurcated { cell: (0, 0) });
+        b.find_solution();
+        assert!(b.log().contains(&SolveEvent::Backtracked));
+    }
+
+    #[test]
+    fn square() {
+        let hints = make_hints("2, 1").unwrap();
+        let mut b = Picross::new_square(hints.get());
+        assert_eq!((b.width(), b.height()), (2, 2));
+        assert_eq!(b.find_solution().unwrap().to_string(), "XX\nX.");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "swapped")]
+    fn swapped_axes() {
+        // Three rows of width three, but only two columns.
+        let row_hints = make_hints("3, 3, 3").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        Picross::new(row_hints.get(), col_hints.get());
+    }
+
+    #[test]
+    fn mismatched_lengths() {
+        // Twenty rows but only fifteen columns: the board is as tall as there are row hints, and
+        // the solver only visits lines that exist.
+        let rows = vec!["1"; 20].join(",");
+        let cols = format!("20{}", ",".repeat(14));
+        let row_hints = make_hints(&rows).unwrap();
+        let col_hints = make_hints(&cols).unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!((b.width(), b.height()), (15, 20));
+        assert_eq!(b.row_permutations(19).len(), 15);
+        assert!(b.row_permutations(20).is_empty());
+        let solution = b.find_solution().unwrap();
+        assert_eq!((solution.width(), solution.height()), (15, 20));
+        assert!((0..20).all(|y| solution.row(y)[0]));
+        assert_eq!(b.find_solution(), None);
+    }
+
+    #[test]
+    fn single_arrangement() {
+        let perms = vec![vec![true, false, true]];
+        assert_eq!(sum_line(&perms), hint::sum_perms(perms.clone().into_iter()));
+        assert_eq!(sum_line(&[]), None);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_solutions() {
+        let row_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let col_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let sequential = b.get_solutions();
+        assert!(sequential.len() > 1);
+        assert_eq!(b.get_solutions_parallel(), sequential);
+
+        let row_hints = make_hints("3, 1, 1").unwrap();
+        let col_hints = make_hints("1, 3, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.get_solutions_parallel().len(), 1);
+        let row_hints = make_hints("2, 2").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert!(b.get_solutions_parallel().is_empty());
+
+        // Both with and without guessing, the connectivity requirement applies.
+        let row_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        b.set_require_connected(true);
+        let sequential = b.get_solutions();
+        assert_eq!(b.get_solutions_parallel(), sequential);
+        let row_hints = make_hints("1, , 1").unwrap();
+        let col_hints = make_hints("1 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        b.set_require_connected(true);
+        assert!(b.get_solutions().is_empty());
+        assert!(b.get_solutions_parallel().is_empty());
+    }
+
+    #[test]
+    fn forced_cell_query() {
+        // Line logic alone solves this one.
+        let row_hints = make_hints("2, 1").unwrap();
+        let col_hints = make_hints("1, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.cell_is_forced(0, 0), Some(true));
+        assert_eq!(b.cell_is_forced(0, 1), Some(false));
+
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.cell_is_forced(1, 1), None);
+
+        let col_hints = make_hints("2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.cell_is_forced(0, 0), None);
+    }
+
+    #[test]
+    fn search_depth() {
+        let row_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let col_hints = make_hints("1 1, 1 1, 1 1, 1 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.current_depth(), 0);
+        let mut depths = vec![];
+        b.find_solution_with(|p| depths.push(p.current_depth()));
+        assert_eq!(depths[0], 0);
+        assert!(depths.iter().any(|&d| d > 0));
+        b.reset();
+        assert_eq!(b.current_depth(), 0);
+    }
+
+    #[test]
+    fn impossible_hints() {
+        let row_hints = make_hints("1, 4, 1").unwrap();
+        let col_hints = make_hints("1, 1, 1").unwrap();
+        assert_eq!(
+            Picross::try_new(row_hints.get(), col_hints.get()).err(),
+            Some(HintError::Impossible {
+                axis: Axis::Row,
+                index: 1
+            })
+        );
+        let row_hints = make_hints("1, 1, 1").unwrap();
+        let col_hints = make_hints("1, 1, 1 1 1").unwrap();
+        assert_eq!(
+            Picross::try_new(row_hints.get(), col_hints.get()).err(),
+            Some(HintError::Impossible {
+                axis: Axis::Col,
+                index: 2
+            })
+        );
+        assert!(Picross::try_new(row_hints.get(), row_hints.get()).is_ok());
+        // Too long to even add up.
+        let row_hints = make_hints("18446744073709551615 1, 1").unwrap();
+        assert_eq!(
+            Picross::try_new(row_hints.get(), col_hints.get()).err(),
+            Some(HintError::Impossible {
+                axis: Axis::Row,
+                index: 0
+            })
+        );
+    }
+
+    #[test]
+    fn verbose_solve() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        let mut out = vec![];
+        let solution = b.solve_verbose(&mut out).unwrap();
+        assert_eq!(solution.unwrap().to_string(), "X.\n.X");
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("-- end of pass 1\nguessing cell (0, 0) = filled\n"));
+        assert!(out.contains("row 0: deduced 1 cells"));
+        assert!(b.log().is_empty());
+    }
+
+    #[test]
+    fn equivalence() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let diagonal = Picross::new(row_hints.get(), row_hints.get());
+        let shorthand_hints = make_hints("1x1, 1").unwrap();
+        let shorthand = Picross::new(shorthand_hints.get(), row_hints.get());
+        assert!(puzzles_equivalent(&diagonal, &shorthand));
+
+        let col_hints = make_hints("2, ").unwrap();
+        let column = Picross::new(row_hints.get(), col_hints.get());
+        assert!(!puzzles_equivalent(&diagonal, &column));
+    }
+
+    #[test]
+    fn unique_needs_guessing() {
+        let row_hints = make_hints("1, 3, 3, 1 1").unwrap();
+        let col_hints = make_hints("1 1, 3, 3, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.solve_strict(), Err(StrictError::RequiresGuessing));
+        b.reset();
+        let solutions = b.get_solutions();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_string(), ".X..\nXXX.\n.XXX\nX.X.");
+        assert!(b.num_backtracks > 0);
+    }
+
+    #[test]
+    fn interrupted_on_solution() {
+        // A puzzle solved by its first pass: stopping there must still report the solution.
+        let row_hints = make_hints("3, 1, 1").unwrap();
+        let col_hints = make_hints("1, 3, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let found = b.search(|_| ControlFlow::Break(()));
+        assert!(matches!(found, Ok(Some(_))));
+        assert_eq!(b.search(|_| ControlFlow::Continue(())), Ok(None));
+    }
+
+    #[test]
+    fn solved_board_access() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), row_hints.get());
+        assert_eq!(b.solved_board(), None);
+        let mut seen = vec![];
+        let solution = b.find_solution_with(|p| seen.push(p.solved_board()));
+        assert_eq!(seen.last(), Some(&solution));
+        assert_eq!(b.solved_board(), solution);
+    }
+
+    #[test]
+    fn full_board_rechecked() {
+        // The row pass leaves both rows open and the column pass fills every cell, which breaks
+        // both row hints.
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.find_solution(), None);
+    }
+
+    #[test]
+    fn solution_checking() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let b = Picross::new(row_hints.get(), row_hints.get());
+        let board = |s: &str| {
+            let cells: Vec<bool> = s.chars().map(|c| c == 'X').collect();
+            Board::new_from_fn(2, cells.len() / 2, |x, y| cells[2 * y + x])
+        };
+        assert_eq!(b.check_solution(&board("X..X")), Ok(()));
+        assert_eq!(b.check_solution(&board("XX..")), Err((Axis::Row, 0)));
+        assert_eq!(b.check_solution(&board("X.X.")), Err((Axis::Col, 0)));
+        assert_eq!(b.check_solution(&board("X.")), Err((Axis::Row, 1)));
+        assert_eq!(b.check_solution(&board("X..X..")), Err((Axis::Row, 2)));
+        let wide = Board::new_from_fn(3, 2, |x, y| x == y);
+        assert_eq!(b.check_solution(&wide), Err((Axis::Col, 2)));
+    }
+
+    #[test]
+    fn hints_from_lines() {
+        let lines = vec!["1 2", "3", "", "2x2"];
+        let from_lines = make_hints_from_lines(&lines).unwrap();
+        assert_eq!(
+            from_lines.canonical(),
+            make_hints("1 2, 3, , 2x2").unwrap().canonical()
+        );
+        let owned = vec![String::from("1"), String::from("0")];
+        assert!(make_hints_from_lines(owned).is_err());
+    }
+
+    #[test]
+    fn distinct_pictures() {
+        // Two diagonals, each the mirror image of the other.
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert_eq!(b.get_solutions().len(), 2);
+        assert_eq!(b.distinct_pictures(), 1);
+        // Of the six 3x3 permutation matrices, the diagonals form one picture and the other four
+        // are all rotations and reflections of each other.
+        let ones = make_hints("1, 1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert_eq!(b.get_solutions().len(), 6);
+        assert_eq!(b.distinct_pictures(), 2);
+    }
+
+    #[test]
+    fn solution_cardinality() {
+        let (rows, cols) = (make_hints("1, 2").unwrap(), make_hints("2, 1").unwrap());
+        let mut b = Picross::new(rows.get(), cols.get());
+        let Cardinality::Unique(solution) = b.solution_cardinality() else {
+            panic!("expected a unique solution");
+        };
+        assert_eq!(solution.to_string(), "X.\nXX");
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert_eq!(b.solution_cardinality(), Cardinality::Multiple);
+        // Asking again restarts the search.
+        assert_eq!(b.solution_cardinality(), Cardinality::Multiple);
+        let twos = make_hints("2, 2").unwrap();
+        let mut b = Picross::new(ones.get(), twos.get());
+        assert_eq!(b.solution_cardinality(), Cardinality::None);
+    }
+
+    #[test]
+    fn exhausted_search() {
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert_eq!(b.get_solutions().len(), 2);
+        let passes = b.num_passes;
+        for _ in 0..3 {
+            assert_eq!(b.find_solution(), None);
+        }
+        assert_eq!(b.num_passes, passes);
+        b.reset();
+        assert_eq!(b.get_solutions().len(), 2);
+
+        let rows = make_hints("2, 2").unwrap();
+        let cols = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(rows.get(), cols.get());
+        assert_eq!(b.find_solution(), None);
+        assert_eq!(b.find_solution(), None);
+    }
+
+    #[test]
+    fn color_hints() {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        enum Color {
+            Blank,
+            Red,
+            Blue,
+        }
+        impl cell::CellLike for Color {
+            fn is_filled(self) -> bool {
+                self != Color::Blank
+            }
+        }
+        use Color::*;
+        let palette = Palette::new().with('r', Red).with('b', Blue);
+        let hints = make_color_hints("1r 1b, , 2b", &palette).unwrap();
+        let one = NonZeroUsize::new(1).unwrap();
+        assert_eq!(hints.get()[0], [(one, Red), (one, Blue)]);
+        assert!(hints.get()[1].is_empty());
+        let perms = hint::colored_permutations(&hints.get()[0], 2, Blank);
+        assert_eq!(perms, [[Red, Blue]]);
+        assert_eq!(
+            make_color_hints("1r, 1g", &palette),
+            Err(HintParseError::UnknownColor {
+                line: 1,
+                token: "1g".to_string()
+            })
+        );
+        assert!(make_hints("1r").is_err());
+    }
+
+    #[test]
+    fn partial_solve() {
+        let row_hints = make_hints("1, 3, 1 1 1").unwrap();
+        let col_hints = make_hints("1, 1, 2, 2, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.solve_until_fraction(0.0), Board::new(5, 3, None));
+        let partial = b.solve_until_fraction(0.1);
+        assert!(b.progress() >= 0.1);
+        assert_eq!(b.num_passes, 1);
+        assert!(partial.as_slice().contains(&None));
+        let full = b.solve_until_fraction(1.0);
+        assert_eq!(full.as_slice().iter().flatten().count(), 15);
+
+        // Line logic can't start on two diagonals, so nothing is determined.
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert_eq!(b.solve_until_fraction(0.8), Board::new(2, 2, None));
+    }
+
+    #[test]
+    fn fresh_clone() {
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        b.set_axis_order(AxisOrder::ColsFirst);
+        let first = b.find_solution().unwrap();
+        assert!(!b.backtrack.is_empty());
+        let mut fresh = b.clone_fresh();
+        assert!(fresh.backtrack.is_empty());
+        assert_eq!((fresh.num_passes, fresh.num_backtracks), (0, 0));
+        assert_eq!(fresh.axis_order, AxisOrder::ColsFirst);
+        assert_eq!(fresh.progress(), 0.0);
+        assert_eq!(fresh.find_solution(), Some(first));
+        assert_eq!(fresh.get_solutions().len(), 1);
+        assert_eq!(b.get_solutions().len(), 1);
+    }
+
+    #[test]
+    fn guessed_cells() {
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        assert!(b.guessed_cells().is_empty());
+        let mut rendered = None;
+        b.find_solution_with(|p| {
+            rendered.get_or_insert_with(|| p.board.render_with_guesses(p.guessed_cells()));
+        });
+        assert_eq!(rendered.as_deref(), Some("??\n??"));
+        assert_eq!(b.guessed_cells(), [(0, 0)]);
+        assert_eq!(b.board.render_with_guesses(b.guessed_cells()), "x.\n.X");
+        // The second solution is the guess's alternative, so nothing is guessed any more.
+        b.find_solution().unwrap();
+        assert!(b.guessed_cells().is_empty());
+        assert_eq!(b.board.render_with_guesses(b.guessed_cells()), ".X\nX.");
+    }
+
+    #[test]
+    fn messy_hints() {
+        let pasted = "\t1\u{a0}2 ,3\u{2003}\u{2003}1,\r\n  4\t";
+        let clean = make_hints("1 2, 3 1, 4").unwrap().canonical();
+        assert_eq!(make_hints(pasted).unwrap().canonical(), clean);
+        assert_eq!(make_hints_with(pasted, true).unwrap().canonical(), clean);
+
+        let trailing = "1 2, 3 1, 4,\u{a0}\n";
+        assert_eq!(make_hints(trailing).unwrap().canonical().len(), 4);
+        assert_eq!(
+            make_hints_with(trailing, true).err(),
+            Some(HintParseError::TrailingComma)
+        );
+        // Blank lines in the middle and an empty string are still fine in strict mode.
+        assert_eq!(
+            make_hints_with("1, , 1", true).unwrap().canonical().len(),
+            3
+        );
+        assert_eq!(make_hints_with("", true).unwrap().canonical().len(), 1);
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn spilled_backtracking() {
+        let row_hints = make_hints("1 1, 1 1, 1 1, 1 1, 1 1").unwrap();
+        let col_hints = make_hints("1 1, 1 1, 1 1, 1 1, 1 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let expected = b.get_solutions();
+        b.reset();
+        b.set_spill_threshold(0);
+        let mut deepest = 0;
+        let mut solutions = vec![];
+        while let Some(solution) = b.find_solution_with(|p| {
+            deepest = deepest.max(p.current_depth());
+            assert!(p.backtrack.len() <= 1);
+        }) {
+            solutions.push(solution);
+            if solutions.len() == 2 {
+                // A clone gets its own copy of the spilled snapshots.
+                assert_eq!(b.clone().get_solutions(), expected[2..]);
+            }
+        }
+        assert!(deepest > 1);
+        assert_eq!(solutions, expected);
+        assert_eq!(b.clone_fresh().get_solutions(), expected);
+    }
+
+    #[test]
+    fn connected_solutions() {
+        let row_hints = make_hints("1, 2, 3").unwrap();
+        let col_hints = make_hints("1, 1, 2, 2").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.get_solutions().len(), 2);
+        b.reset();
+        b.set_require_connected(true);
+        let solutions = b.get_solutions();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].to_string(), "...X\n..XX\nXXX.");
+
+        // Both diagonals are disconnected.
+        let ones = make_hints("1, 1").unwrap();
+        let mut b = Picross::new(ones.get(), ones.get());
+        b.set_require_connected(true);
+        assert_eq!(b.find_solution(), None);
+    }
+
+    #[test]
+    fn precomputed_perms() {
+        let row_hints = make_hints("1, 3, 1 1 1").unwrap();
+        let col_hints = make_hints("1, 1, 2, 2, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        let perms = |lines: usize, get: &dyn Fn(usize) -> Vec<Vec<Cell>>| -> SectionPerms {
+            (0..lines).map(get).collect()
+        };
+        let rows = perms(3, &|y| b.row_permutations(y).to_vec());
+        let cols = perms(5, &|x| b.col_permutations(x).to_vec());
+        let mut copy = Picross::from_perms((5, 3), rows.clone(), cols.clone()).unwrap();
+        assert_eq!(copy.row_hints, b.row_hints);
+        assert_eq!(copy.col_hints, b.col_hints);
+        assert_eq!(copy.get_solutions(), b.get_solutions());
+
+        assert_eq!(
+            Picross::from_perms((3, 5), rows.clone(), cols.clone()).err(),
+            Some(PermsError::LineCount {
+                axis: Axis::Row,
+                expected: 5,
+                actual: 3
+            })
+        );
+        let mut short = cols;
+        short[4][0].pop();
+        assert_eq!(
+            Picross::from_perms((5, 3), rows, short).err(),
+            Some(PermsError::PermLength {
+                axis: Axis::Col,
+                index: 4,
+                expected: 3,
+                actual: 2
+            })
+        );
+    }
+
+    #[test]
+    fn feasibility() {
+        let row_hints = make_hints("1, 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        assert!(Picross::new(row_hints.get(), col_hints.get()).quick_feasibility());
+        let col_hints = make_hints("2, 1").unwrap();
+        assert!(!Picross::new(row_hints.get(), col_hints.get()).quick_feasibility());
+        let row_hints = make_hints("1 1").unwrap();
+        let col_hints = make_hints("1, 1").unwrap();
+        assert!(!Picross::new(row_hints.get(), col_hints.get()).quick_feasibility());
+        // The debug guard in `new` must not overflow on hints this long.
+        let row_hints = make_hints("18446744073709551615 1, 1").unwrap();
+        let mut b = Picross::new(row_hints.get(), col_hints.get());
+        assert!(!b.quick_feasibility());
+        assert_eq!(b.find_solution(), None);
+    }
+
+    #[test]
+    fn cell_balance() {
+        let row_hints = make_hints("1 2, 3").unwrap();
+        let col_hints = make_hints("2, 2, 2").unwrap();
+        let b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.filled_cell_balance(), Ok(6));
+        let col_hints = make_hints("2, 1, 2").unwrap();
+        let b = Picross::new(row_hints.get(), col_hints.get());
+        assert_eq!(b.filled_cell_balance(), Err((6, 5)));
+    }
+}
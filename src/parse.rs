@@ -0,0 +1,349 @@
+use crate::board::Board;
+use crate::cell::Cell;
+use crate::puzzle::Puzzle;
+use std::fmt;
+use std::num::NonZeroUsize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input ended partway through a puzzle.
+    UnexpectedEof,
+    /// A token on the given (1-based) line isn't a valid number.
+    InvalidNumber { line: usize, token: String },
+    /// A clue on the given line carries a color, which isn't supported yet.
+    Colored { line: usize, token: String },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::InvalidNumber { line, token } => {
+                write!(f, "line {}: invalid number `{}`", line, token)
+            }
+            Self::Colored { line, token } => {
+                write!(
+                    f,
+                    "line {}: colored clue `{}` is not supported",
+                    line, token
+                )
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HintParseError {
+    /// A run length that isn't a positive integer.
+    InvalidRun(String),
+    /// An `NxM` token where either side isn't a positive integer.
+    InvalidShorthand(String),
+    /// A whole puzzle without the `;` between its row and column hints.
+    MissingSeparator,
+    /// A comma after the last line, which strict parsing doesn't read as a blank line.
+    TrailingComma,
+    /// A colored clue whose letter isn't in the palette, or that has no letter. `line` counts
+    /// from 0, like the index of the row or column.
+    UnknownColor { line: usize, token: String },
+}
+
+impl fmt::Display for HintParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRun(token) => write!(f, "invalid run length `{}`", token),
+            Self::InvalidShorthand(token) => write!(
+                f,
+                "invalid shorthand `{}`, expected `NxM` for M runs of length N",
+                token
+            ),
+            Self::MissingSeparator => write!(f, "expected `;` between row and column hints"),
+            Self::TrailingComma => write!(f, "trailing comma after the last line"),
+            Self::UnknownColor { line, token } => {
+                write!(f, "hint {}: unknown color in clue `{}`", line, token)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PbmError {
+    /// The file doesn't start with `P1`. Binary `P4` bitmaps aren't supported.
+    UnsupportedFormat,
+    /// The input ended before the header or every pixel was read.
+    UnexpectedEof,
+    /// The width or height isn't a valid number, or is zero while the other isn't.
+    InvalidDimension(String),
+    /// The dimensions describe more pixels than fit in memory.
+    TooLarge,
+    /// A pixel that isn't `0` or `1`.
+    InvalidPixel(char),
+    /// More pixels follow the last row.
+    TrailingData,
+}
+
+impl fmt::Display for PbmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedFormat => write!(f, "not an ASCII (P1) bitmap"),
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::InvalidDimension(token) => write!(f, "invalid dimension `{}`", token),
+            Self::TooLarge => write!(f, "bitmap dimensions are too large"),
+            Self::InvalidPixel(c) => write!(f, "invalid pixel `{}`", c.escape_default()),
+            Self::TrailingData => write!(f, "unexpected data after the last pixel"),
+        }
+    }
+}
+
+/// The largest `M` an `NxM` token may repeat a run, far more runs than any real line has room
+/// for, so a short token can't ask for an enormous allocation.
+const MAX_SHORTHAND_RUNS: usize = 4_096;
+
+/// Parses a single hint token: either a run length, or `NxM` for `M` runs of length `N`.
+/// `M` may be at most [`MAX_SHORTHAND_RUNS`].
+pub fn parse_runs(token: &str) -> Result<Vec<NonZeroUsize>, HintParseError> {
+    match token.split_once('x') {
+        None => token
+            .parse()
+            .map(|n| vec![n])
+            .map_err(|_| HintParseError::InvalidRun(token.to_string())),
+        Some((n, m)) => {
+            let invalid = || HintParseError::InvalidShorthand(token.to_string());
+            let n: NonZeroUsize = n.parse().map_err(|_| invalid())?;
+            let m: NonZeroUsize = m.parse().map_err(|_| invalid())?;
+            if m.get() > MAX_SHORTHAND_RUNS {
+                return Err(invalid());
+            }
+            Ok(vec![n; m.get()])
+        }
+    }
+}
+
+/// The colors that the letters of colored clues stand for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette<C> {
+    colors: Vec<(char, C)>,
+}
+
+impl<C> Default for Palette<C> {
+    fn default() -> Self {
+        Self { colors: vec![] }
+    }
+}
+
+impl<C: Copy> Palette<C> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds `letter` for `color`, replacing whatever color it stood for before.
+    pub fn with(mut self, letter: char, color: C) -> Self {
+        self.colors.retain(|&(l, _)| l != letter);
+        self.colors.push((letter, color));
+        self
+    }
+    pub fn get(&self, letter: char) -> Option<C> {
+        self.colors
+            .iter()
+            .find_map(|&(l, color)| (l == letter).then_some(color))
+    }
+}
+
+/// Parses a colored clue token such as `3b`: a run length followed by the letter of its color.
+pub fn parse_color_run<C: Copy>(
+    line: usize,
+    token: &str,
+    palette: &Palette<C>,
+) -> Result<(NonZeroUsize, C), HintParseError> {
+    let unknown = || HintParseError::UnknownColor {
+        line,
+        token: token.to_string(),
+    };
+    let letter = token
+        .chars()
+        .next_back()
+        .filter(|c| c.is_alphabetic())
+        .ok_or_else(unknown)?;
+    let len = token[..token.len() - letter.len_utf8()]
+        .parse()
+        .map_err(|_| HintParseError::InvalidRun(token.to_string()))?;
+    let color = palette.get(letter).ok_or_else(unknown)?;
+    Ok((len, color))
+}
+
+/// Parses every puzzle in a `.cwd` file.
+///
+/// Each puzzle is the row count and the column count on their own lines, followed by one line of
+/// whitespace-separated run lengths per row and then per column. A line reading `0` is a blank
+/// line. Puzzles in the same file are separated by blank lines.
+pub fn parse_cwd(input: &str) -> Result<Vec<Puzzle>, ParseError> {
+    let mut lines = input.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    let mut puzzles = vec![];
+    loop {
+        let Some((line, first)) = lines.by_ref().find(|(_, l)| !l.is_empty()) else {
+            return Ok(puzzles);
+        };
+        let height = parse_count(line, first)?;
+        let (line, second) = lines.next().ok_or(ParseError::UnexpectedEof)?;
+        let width = parse_count(line, second)?;
+        let mut clues = |n| -> Result<Vec<_>, ParseError> {
+            (0..n)
+                .map(|_| {
+                    let (line, l) = lines.next().ok_or(ParseError::UnexpectedEof)?;
+                    parse_clue_line(line, l)
+                })
+                .collect()
+        };
+        let rows = clues(height)?;
+        let cols = clues(width)?;
+        puzzles.push(Puzzle::new(rows, cols));
+    }
+}
+
+/// Reads an ASCII (`P1`) portable bitmap, where `1` is a filled cell.
+///
+/// Comments run from `#` to the end of the line. Pixels may be separated by any amount of
+/// whitespace, including none.
+pub fn parse_pbm(bytes: &[u8]) -> Result<Board<Cell>, PbmError> {
+    let mut tokens = bytes
+        .split(|&b| b == b'\n')
+        .map(|line| line.split(|&b| b == b'#').next().unwrap_or_default())
+        .flat_map(|line| line.split(u8::is_ascii_whitespace))
+        .filter(|token| !token.is_empty());
+    if tokens.next() != Some(b"P1") {
+        return Err(PbmError::UnsupportedFormat);
+    }
+    let mut dimension = || -> Result<usize, PbmError> {
+        let token = tokens.next().ok_or(PbmError::UnexpectedEof)?;
+        let token = String::from_utf8_lossy(token);
+        token
+            .parse()
+            .map_err(|_| PbmError::InvalidDimension(token.into_owned()))
+    };
+    let width = dimension()?;
+    let height = dimension()?;
+    let len = width.checked_mul(height).ok_or(PbmError::TooLarge)?;
+    if len == 0 && width != height {
+        return Err(PbmError::InvalidDimension("0".to_string()));
+    }
+    let mut pixels = tokens.flatten().map(|&b| match b {
+        b'0' => Ok(false),
+        b'1' => Ok(true),
+        _ => Err(PbmError::InvalidPixel(char::from(b))),
+    });
+    let cells = pixels
+        .by_ref()
+        .take(len)
+        .collect::<Result<Vec<Cell>, _>>()?;
+    if cells.len() < len {
+        return Err(PbmError::UnexpectedEof);
+    }
+    if pixels.next().is_some() {
+        return Err(PbmError::TrailingData);
+    }
+    Ok(Board::from_row_major(width, height, cells))
+}
+
+fn parse_count(line: usize, token: &str) -> Result<usize, ParseError> {
+    token.parse().map_err(|_| ParseError::InvalidNumber {
+        line,
+        token: token.to_string(),
+    })
+}
+
+fn parse_clue_line(line: usize, l: &str) -> Result<Vec<NonZeroUsize>, ParseError> {
+    l.split_whitespace()
+        .filter(|&token| token != "0")
+        .map(|token| {
+            if token.ends_with(|c: char| c.is_ascii_alphabetic()) {
+                return Err(ParseError::Colored {
+                    line,
+                    token: token.to_string(),
+                });
+            }
+            token.parse().map_err(|_| ParseError::InvalidNumber {
+                line,
+                token: token.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u(x: usize) -> NonZeroUsize {
+        NonZeroUsize::new(x).unwrap()
+    }
+
+    #[test]
+    fn cwd_multiple_puzzles() {
+        let input = "2\n3\n1 1\n0\n1\n0\n1\n\n1\n1\n1\n1\n";
+        let puzzles = parse_cwd(input).unwrap();
+        assert_eq!(puzzles.len(), 2);
+        assert_eq!(puzzles[0].rows, [vec![u(1), u(1)], vec![]]);
+        assert_eq!(puzzles[0].cols, [vec![u(1)], vec![], vec![u(1)]]);
+        assert_eq!(puzzles[1], Puzzle::new(vec![vec![u(1)]], vec![vec![u(1)]]));
+        assert!(puzzles[0].picross().find_solution().is_some());
+    }
+
+    #[test]
+    fn cwd_errors() {
+        assert_eq!(parse_cwd("2\n2\n1\n"), Err(ParseError::UnexpectedEof));
+        assert_eq!(
+            parse_cwd("1\n1\n1x\n1\n"),
+            Err(ParseError::Colored {
+                line: 3,
+                token: "1x".to_string()
+            })
+        );
+        assert_eq!(
+            parse_cwd("1\nabc\n"),
+            Err(ParseError::InvalidNumber {
+                line: 2,
+                token: "abc".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn color_runs() {
+        let palette = Palette::new().with('r', 1).with('b', 2).with('r', 3);
+        assert_eq!(parse_color_run(0, "3b", &palette), Ok((u(3), 2)));
+        assert_eq!(parse_color_run(0, "12r", &palette), Ok((u(12), 3)));
+        let unknown = |token: &str| HintParseError::UnknownColor {
+            line: 4,
+            token: token.to_string(),
+        };
+        assert_eq!(parse_color_run(4, "2g", &palette), Err(unknown("2g")));
+        assert_eq!(parse_color_run(4, "2", &palette), Err(unknown("2")));
+        assert_eq!(
+            parse_color_run(4, "0b", &palette),
+            Err(HintParseError::InvalidRun("0b".to_string()))
+        );
+    }
+
+    #[test]
+    fn pbm() {
+        let input = b"P1\n# a tiny arrow\n3 2\n0 1 0\n111\n";
+        let board = parse_pbm(input).unwrap();
+        assert_eq!(board.to_string(), ".X.\nXXX");
+        let puzzle = crate::puzzle::hints_from_board(&board);
+        assert_eq!(puzzle.rows, [vec![u(1)], vec![u(3)]]);
+        assert_eq!(puzzle.cols, [vec![u(1)], vec![u(2)], vec![u(1)]]);
+        assert_eq!(puzzle.picross().find_solution(), Some(board));
+
+        assert_eq!(parse_pbm(b"P4 1 1 0"), Err(PbmError::UnsupportedFormat));
+        assert_eq!(parse_pbm(b"P1 2 2 101"), Err(PbmError::UnexpectedEof));
+        assert_eq!(parse_pbm(b"P1 1 1 10"), Err(PbmError::TrailingData));
+        assert_eq!(parse_pbm(b"P1 1 1 2"), Err(PbmError::InvalidPixel('2')));
+        assert_eq!(
+            parse_pbm(b"P1 0 99999999999999"),
+            Err(PbmError::InvalidDimension("0".to_string()))
+        );
+        assert_eq!(parse_pbm(b"P1 0 0").unwrap().width(), 0);
+        assert_eq!(
+            parse_pbm(b"P1 x 1"),
+            Err(PbmError::InvalidDimension("x".to_string()))
+        );
+    }
+}
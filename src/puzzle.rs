@@ -0,0 +1,328 @@
+use crate::board::{self, Board, DimensionMismatch};
+use crate::cell::Cell;
+use crate::hint::{self, Hint};
+use crate::parse::HintParseError;
+use crate::{make_hints, Picross};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+
+/// The clues of a single puzzle, independent of the format they were read from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Puzzle {
+    pub rows: Vec<Vec<NonZeroUsize>>,
+    pub cols: Vec<Vec<NonZeroUsize>>,
+}
+
+impl Puzzle {
+    pub fn new(rows: Vec<Vec<NonZeroUsize>>, cols: Vec<Vec<NonZeroUsize>>) -> Self {
+        Self { rows, cols }
+    }
+    pub fn picross(&self) -> Picross {
+        Picross::new(&hints(&self.rows), &hints(&self.cols))
+    }
+    /// Finds a solution, or `None` if the puzzle has none.
+    pub fn solve(&self) -> Option<Board<Cell>> {
+        self.picross().find_solution()
+    }
+    /// A hash of both hint lists, equal for equal puzzles no matter which format they were read
+    /// from. It is only stable for a given build, so don't persist it.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+    /// A short identifier for the puzzle's hints, as 16 hex digits, that is the same on every
+    /// build and platform, so unlike [`Puzzle::fingerprint`] it can be stored.
+    ///
+    /// This is a 64-bit FNV-1a content hash of the line counts and run lengths. It's fine for
+    /// deduplication, but collisions can be constructed on purpose, so it's no proof that two
+    /// puzzles are the same.
+    pub fn id(&self) -> String {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        let mut hash = OFFSET_BASIS;
+        let mut write = |n: usize| {
+            for byte in (n as u64).to_le_bytes() {
+                hash = (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+            }
+        };
+        for lines in [&self.rows, &self.cols] {
+            write(lines.len());
+            for line in lines {
+                write(line.len());
+                line.iter().for_each(|run| write(run.get()));
+            }
+        }
+        format!("{:016x}", hash)
+    }
+    /// Draws `board` with the row hints to its left and the column hints stacked above it.
+    ///
+    /// Every glyph is taken to be `glyphs.width` terminal columns wide, and each column's hints
+    /// are right-aligned over its cells, so wide glyphs like `█` still line up with the header.
+    pub fn render_with_hints(
+        &self,
+        board: &Board<Option<Cell>>,
+        glyphs: Glyphs,
+    ) -> Result<String, DimensionMismatch> {
+        let expected = (self.cols.len(), self.rows.len());
+        let actual = (board.width(), board.height());
+        if actual != expected {
+            return Err(DimensionMismatch { expected, actual });
+        }
+        let runs = |line: &[NonZeroUsize]| -> Vec<String> {
+            if line.is_empty() {
+                vec!["0".to_string()]
+            } else {
+                line.iter().map(ToString::to_string).collect()
+            }
+        };
+        let row_hints: Vec<String> = self.rows.iter().map(|l| runs(l).join(" ")).collect();
+        let col_hints: Vec<Vec<String>> = self.cols.iter().map(|l| runs(l)).collect();
+        let margin = row_hints.iter().map(String::len).max().unwrap_or(0);
+        let slot = col_hints
+            .iter()
+            .flatten()
+            .map(String::len)
+            .chain([glyphs.width])
+            .max()
+            .unwrap_or(1);
+        let depth = col_hints.iter().map(Vec::len).max().unwrap_or(0);
+        let mut lines = vec![];
+        for i in 0..depth {
+            let numbers: Vec<String> = col_hints
+                .iter()
+                .map(|hints| {
+                    let hint = (i + hints.len())
+                        .checked_sub(depth)
+                        .map_or("", |j| &hints[j]);
+                    format!("{:>w$}", hint, w = slot)
+                })
+                .collect();
+            lines.push(format!("{:w$} {}", "", numbers.join(" "), w = margin));
+        }
+        let padding = " ".repeat(slot - glyphs.width);
+        for (y, hint) in row_hints.iter().enumerate() {
+            let cells: Vec<String> = board
+                .row(y)
+                .iter()
+                .map(|&cell| format!("{}{}", glyphs.get(cell), padding))
+                .collect();
+            lines.push(format!("{:>w$} {}", hint, cells.join(" "), w = margin));
+        }
+        Ok(lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+/// The characters [`Puzzle::render_with_hints`] draws cells with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Glyphs {
+    pub filled: char,
+    pub empty: char,
+    pub unknown: char,
+    /// How many terminal columns each of the glyphs takes up.
+    pub width: usize,
+}
+
+impl Glyphs {
+    fn get(&self, cell: Option<Cell>) -> char {
+        match cell {
+            Some(true) => self.filled,
+            Some(false) => self.empty,
+            None => self.unknown,
+        }
+    }
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            filled: 'X',
+            empty: '.',
+            unknown: '?',
+            width: 1,
+        }
+    }
+}
+
+fn hints(lines: &[Vec<NonZeroUsize>]) -> Vec<Hint<'_>> {
+    lines.iter().map(|l| Hint::new(l)).collect()
+}
+
+/// How many boards [`generate_unique`] tries before giving up.
+const GENERATE_ATTEMPTS: usize = 1_000;
+
+/// [`generate_unique`] ran out of attempts without finding a uniquely solvable puzzle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenerationFailed;
+
+/// Generates a puzzle with exactly one solution from a random `width` by `height` picture where
+/// each cell is filled with probability `density`. The same arguments always give the same puzzle.
+///
+/// While the puzzle is ambiguous, a cell that two of its solutions disagree on is toggled and
+/// the hints are derived again. A board with no cells can't be generated.
+pub fn generate_unique(
+    width: usize,
+    height: usize,
+    density: f64,
+    seed: u64,
+) -> Result<Puzzle, GenerationFailed> {
+    if width == 0 || height == 0 {
+        return Err(GenerationFailed);
+    }
+    let mut rng = SplitMix64(seed);
+    let mut board = Board::new_from_fn(width, height, |_, _| rng.next_f64() < density);
+    for _ in 0..GENERATE_ATTEMPTS {
+        let puzzle = hints_from_board(&board);
+        let solutions = puzzle.picross().get_solutions_capped(2);
+        if solutions.len() == 1 {
+            return Ok(puzzle);
+        }
+        let differences = board::solution_differences(&solutions);
+        let ambiguous: Vec<_> = differences
+            .iter_positions()
+            .filter(|&(_, _, &differs)| differs)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        // Hints derived from a board always have it as a solution, so this only happens if
+        // they somehow have none.
+        if ambiguous.is_empty() {
+            return Err(GenerationFailed);
+        }
+        let (x, y) = ambiguous[rng.next_below(ambiguous.len())];
+        let cell = board.pos_mut(x, y);
+        *cell = !*cell;
+    }
+    Err(GenerationFailed)
+}
+
+/// A tiny seedable generator, so puzzles can be reproduced without pulling in a dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    /// A uniform value in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    fn next_below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Parses `rows;cols`, each side written as for [`make_hints`].
+impl TryFrom<&str> for Puzzle {
+    type Error = HintParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (rows, cols) = s.split_once(';').ok_or(HintParseError::MissingSeparator)?;
+        Ok(Self::new(
+            make_hints(rows)?.canonical(),
+            make_hints(cols)?.canonical(),
+        ))
+    }
+}
+
+/// Derives the hints that describe `board`. Other boards may share them.
+pub fn hints_from_board(board: &Board<Cell>) -> Puzzle {
+    let rows = (0..board.height())
+        .map(|y| hint::line_runs(board.row(y)))
+        .collect();
+    let cols = (0..board.width())
+        .map(|x| hint::line_runs(&board.col(x)))
+        .collect();
+    Puzzle::new(rows, cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_generation() {
+        for seed in 0..20 {
+            let puzzle = generate_unique(6, 5, 0.5, seed).unwrap();
+            assert_eq!((puzzle.cols.len(), puzzle.rows.len()), (6, 5));
+            assert!(puzzle.picross().is_unique());
+            assert_eq!(generate_unique(6, 5, 0.5, seed), Ok(puzzle));
+        }
+        let blank = generate_unique(3, 3, 0.0, 7).unwrap();
+        assert!(blank.rows.iter().all(Vec::is_empty));
+        assert_eq!(generate_unique(0, 3, 0.5, 1), Err(GenerationFailed));
+        assert_eq!(generate_unique(3, 0, 0.5, 1), Err(GenerationFailed));
+    }
+
+    #[test]
+    fn inverted_puzzle() {
+        let picture = generate_unique(5, 5, 0.5, 3).unwrap().solve().unwrap();
+        let mut negative = picture.clone();
+        negative.invert();
+        let inverted = hints_from_board(&negative);
+        assert_ne!(inverted, hints_from_board(&picture));
+        let mut solved = inverted.solve().unwrap();
+        assert_eq!(solved, negative);
+        solved.invert();
+        assert_eq!(solved, picture);
+    }
+
+    #[test]
+    fn render_with_hints() {
+        let puzzle: Puzzle = "1, 2; 2, 1".try_into().unwrap();
+        let board = Board::new_from_fn(2, 2, |x, y| (x == 0 || y == 1).then_some(true));
+        let narrow = puzzle.render_with_hints(&board, Glyphs::default()).unwrap();
+        assert_eq!(narrow, "  2 1\n1 X ?\n2 X X");
+        let blocks = Glyphs {
+            filled: '█',
+            empty: '░',
+            unknown: '▒',
+            width: 2,
+        };
+        let wide = puzzle.render_with_hints(&board, blocks).unwrap();
+        // Each hint ends in the same terminal column as the glyphs under it.
+        assert_eq!(wide, "   2  1\n1 █ ▒\n2 █ █");
+        let tall: Puzzle = "1, , 1 1; 3, , 1 12".try_into().unwrap();
+        let blank = Board::new_default(3, 3);
+        assert_eq!(
+            tall.render_with_hints(&blank, blocks).unwrap(),
+            "           1\n     3  0 12\n  1 ▒ ▒ ▒\n  0 ▒ ▒ ▒\n1 1 ▒ ▒ ▒"
+        );
+        assert!(tall.render_with_hints(&board, blocks).is_err());
+    }
+
+    #[test]
+    fn stable_id() {
+        let puzzle: Puzzle = "3, 1, 1; 1, 3, 1".try_into().unwrap();
+        let shorthand: Puzzle = "3,1,1;1,3,1".try_into().unwrap();
+        assert_eq!(puzzle.id(), shorthand.id());
+        assert_eq!(puzzle.id(), "6afdbc7d73998285");
+        // Moving a run to another line changes the ID.
+        let moved: Puzzle = "3 1, 1; 1, 3, 1".try_into().unwrap();
+        assert_ne!(moved.id(), puzzle.id());
+        assert_ne!(Puzzle::default().id(), puzzle.id());
+    }
+
+    #[test]
+    fn from_str() {
+        let puzzle: Puzzle = "3, 1, 1; 1, 3, 1".try_into().unwrap();
+        assert_eq!(puzzle.rows.len(), 3);
+        assert_eq!(puzzle.solve().unwrap().to_string(), "XXX\n.X.\n.X.");
+        assert_eq!(
+            Puzzle::try_from("1, 1"),
+            Err(HintParseError::MissingSeparator)
+        );
+        assert_eq!(
+            Puzzle::try_from("1; a"),
+            Err(HintParseError::InvalidRun("a".to_string()))
+        );
+    }
+}
@@ -0,0 +1,281 @@
+//! Moves the oldest backtrack snapshots to a temporary file once they take up too much memory.
+
+use crate::board::Board;
+use crate::{SectionPerms, Snapshot};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// The backtrack snapshots that don't fit under the threshold, along with the bookkeeping that
+/// decides when to move more of them out of memory.
+#[derive(Debug, Default)]
+pub struct Spill {
+    threshold: Option<usize>,
+    // The estimated size of the snapshots still in memory.
+    resident_bytes: usize,
+    file: Option<SpillFile>,
+}
+
+impl Spill {
+    pub fn set_threshold(&mut self, bytes: Option<usize>) {
+        self.threshold = bytes;
+    }
+    pub const fn threshold(&self) -> Option<usize> {
+        self.threshold
+    }
+    /// How many snapshots are on disk.
+    pub fn len(&self) -> usize {
+        self.file.as_ref().map_or(0, |file| file.offsets.len())
+    }
+    /// Pushes `snapshot` onto `backtrack`, then moves the oldest snapshots to disk until the rest
+    /// fit under the threshold. The newest snapshot always stays in memory.
+    pub fn push(&mut self, backtrack: &mut Vec<Snapshot>, snapshot: Snapshot) -> io::Result<()> {
+        self.resident_bytes += snapshot_bytes(&snapshot);
+        backtrack.push(snapshot);
+        let Some(threshold) = self.threshold else {
+            return Ok(());
+        };
+        let mut spilled = 0;
+        while self.resident_bytes > threshold && spilled + 1 < backtrack.len() {
+            self.resident_bytes -= snapshot_bytes(&backtrack[spilled]);
+            spilled += 1;
+        }
+        if spilled > 0 {
+            let file = match &mut self.file {
+                Some(file) => file,
+                None => self.file.insert(SpillFile::create()?),
+            };
+            for snapshot in backtrack.drain(..spilled) {
+                file.push(&snapshot)?;
+            }
+        }
+        Ok(())
+    }
+    /// Pops the newest snapshot, reading it back from disk once none are left in memory.
+    pub fn pop(&mut self, backtrack: &mut Vec<Snapshot>) -> io::Result<Option<Snapshot>> {
+        if let Some(snapshot) = backtrack.pop() {
+            self.resident_bytes -= snapshot_bytes(&snapshot);
+            return Ok(Some(snapshot));
+        }
+        match &mut self.file {
+            Some(file) => file.pop(),
+            None => Ok(None),
+        }
+    }
+    /// Forgets every snapshot, in memory or on disk. The threshold is kept.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.resident_bytes = 0;
+        match &mut self.file {
+            Some(file) => file.clear(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Clone for Spill {
+    /// Copies the spilled snapshots into a file of the clone's own.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the copy can't be written.
+    fn clone(&self) -> Self {
+        let file = self.file.as_ref().map(|file| {
+            file.try_clone()
+                .expect("failed to copy the backtrack spill file")
+        });
+        Self {
+            threshold: self.threshold,
+            resident_bytes: self.resident_bytes,
+            file,
+        }
+    }
+}
+
+/// A temporary file used as a stack of snapshots. It is deleted when dropped.
+#[derive(Debug)]
+struct SpillFile {
+    path: PathBuf,
+    file: File,
+    // Where each snapshot starts, oldest first.
+    offsets: Vec<u64>,
+}
+
+impl SpillFile {
+    fn create() -> io::Result<Self> {
+        let name = format!(
+            "picross-spill-{}-{}",
+            process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            offsets: vec![],
+        })
+    }
+    fn try_clone(&self) -> io::Result<Self> {
+        let mut copy = Self::create()?;
+        let mut source = &self.file;
+        source.seek(SeekFrom::Start(0))?;
+        io::copy(&mut source, &mut copy.file)?;
+        copy.offsets.clone_from(&self.offsets);
+        Ok(copy)
+    }
+    fn push(&mut self, snapshot: &Snapshot) -> io::Result<()> {
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&encode(snapshot))?;
+        self.offsets.push(offset);
+        Ok(())
+    }
+    fn pop(&mut self) -> io::Result<Option<Snapshot>> {
+        let Some(offset) = self.offsets.pop() else {
+            return Ok(None);
+        };
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![];
+        self.file.read_to_end(&mut bytes)?;
+        self.file.set_len(offset)?;
+        decode(&bytes).map(Some)
+    }
+    fn clear(&mut self) -> io::Result<()> {
+        self.offsets.clear();
+        self.file.set_len(0)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        // Nothing useful can be done if the file is already gone.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Roughly how much memory a snapshot holds: a byte per cell of its board and permutations, plus
+/// the header of each `Vec`.
+fn snapshot_bytes((board, rows_perms, cols_perms): &Snapshot) -> usize {
+    const VEC_HEADER: usize = 3 * size_of::<usize>();
+    let perms_bytes = |perms: &SectionPerms| -> usize {
+        perms
+            .iter()
+            .map(|line| VEC_HEADER + line.iter().map(|p| VEC_HEADER + p.len()).sum::<usize>())
+            .sum()
+    };
+    board.as_slice().len() + perms_bytes(rows_perms) + perms_bytes(cols_perms)
+}
+
+// The board's width and height, a byte per cell, then for each axis the number of lines and for
+// each line the number of permutations, their length and a byte per cell. Numbers are
+// little-endian `u64`s.
+fn encode((board, rows_perms, cols_perms): &Snapshot) -> Vec<u8> {
+    let mut bytes = vec![];
+    let number = |bytes: &mut Vec<u8>, n: usize| bytes.extend((n as u64).to_le_bytes());
+    number(&mut bytes, board.width());
+    number(&mut bytes, board.height());
+    bytes.extend(board.as_slice().iter().map(|&cell| match cell {
+        None => 0,
+        Some(false) => 1,
+        Some(true) => 2,
+    }));
+    for perms in [rows_perms, cols_perms] {
+        number(&mut bytes, perms.len());
+        for line in perms {
+            number(&mut bytes, line.len());
+            number(&mut bytes, line.first().map_or(0, Vec::len));
+            for perm in line {
+                bytes.extend(perm.iter().map(|&cell| u8::from(cell)));
+            }
+        }
+    }
+    bytes
+}
+
+fn decode(bytes: &[u8]) -> io::Result<Snapshot> {
+    let mut reader = Decoder { bytes };
+    let width = reader.number()?;
+    let height = reader.number()?;
+    let len = width.checked_mul(height).ok_or_else(corrupt)?;
+    let cells = reader
+        .take(len)?
+        .iter()
+        .map(|&b| match b {
+            0 => Ok(None),
+            1 => Ok(Some(false)),
+            2 => Ok(Some(true)),
+            _ => Err(corrupt()),
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let board = Board::new_from_fn(width, height, |x, y| cells[y * width + x]);
+    let mut section = || -> io::Result<SectionPerms> {
+        (0..reader.number()?)
+            .map(|_| {
+                let count = reader.number()?;
+                let len = reader.number()?;
+                (0..count)
+                    .map(|_| Ok(reader.take(len)?.iter().map(|&b| b != 0).collect()))
+                    .collect()
+            })
+            .collect()
+    };
+    let rows_perms = section()?;
+    let cols_perms = section()?;
+    if !reader.bytes.is_empty() {
+        return Err(corrupt());
+    }
+    Ok((board, rows_perms, cols_perms))
+}
+
+fn corrupt() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "corrupt backtrack spill file")
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let (taken, rest) = self.bytes.split_at_checked(n).ok_or_else(corrupt)?;
+        self.bytes = rest;
+        Ok(taken)
+    }
+    fn number(&mut self) -> io::Result<usize> {
+        let bytes = self.take(8)?.try_into().map_err(|_| corrupt())?;
+        usize::try_from(u64::from_le_bytes(bytes)).map_err(|_| corrupt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let board = Board::new_from_fn(3, 2, |x, y| (x != 1).then_some(x == y));
+        let rows_perms = vec![vec![vec![true, false, false]], vec![]];
+        let cols_perms = vec![vec![vec![true, false], vec![false, true]]; 3];
+        let snapshot = (board, rows_perms, cols_perms);
+        let mut file = SpillFile::create().unwrap();
+        file.push(&snapshot).unwrap();
+        file.push(&(Board::new_default(0, 0), vec![], vec![]))
+            .unwrap();
+        let copy = file.try_clone().unwrap();
+        assert_eq!(file.pop().unwrap().unwrap().0.width(), 0);
+        assert_eq!(file.pop().unwrap(), Some(snapshot.clone()));
+        assert_eq!(file.pop().unwrap(), None);
+        let path = copy.path.clone();
+        assert!(path.exists());
+        drop(copy);
+        assert!(!path.exists());
+        assert!(decode(&encode(&snapshot)[1..]).is_err());
+    }
+}
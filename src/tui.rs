@@ -0,0 +1,58 @@
+use crate::cell::Cell;
+use crate::{Board, Picross};
+use std::io::{self, BufRead, Write};
+use std::thread;
+use std::time::Duration;
+
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+const fn glyph(x: Option<Cell>, guessed: bool) -> char {
+    match x {
+        Some(true) if guessed => '▒',
+        Some(true) => '█',
+        Some(false) => '·',
+        None => '?',
+    }
+}
+
+fn render(picross: &Picross, out: &mut impl Write, fps: Option<u32>) -> io::Result<()> {
+    write!(out, "{}", CLEAR)?;
+    for y in 0..picross.height() {
+        let guessed = |x| picross.guessed_cells().contains(&(x, y));
+        let row: String = (picross.board.row(y).iter().enumerate())
+            .map(|(x, &v)| glyph(v, guessed(x)))
+            .collect();
+        writeln!(out, "{}", row)?;
+    }
+    write!(
+        out,
+        "Backtracks: {}  Depth: {}",
+        picross.num_backtracks,
+        picross.current_depth()
+    )?;
+    if fps.is_none() {
+        write!(out, " (press Enter to step)")?;
+    }
+    writeln!(out)?;
+    out.flush()
+}
+
+/// Runs the solver until the next solution, redrawing the board after every propagation pass.
+///
+/// With `fps` set the passes are animated at that rate, otherwise each pass waits for a line on
+/// stdin.
+pub fn watch(picross: &mut Picross, fps: Option<u32>) -> Option<Board<Cell>> {
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let stdout = io::stdout();
+    picross.find_solution_with(|p| {
+        // Drawing is best-effort; a broken terminal shouldn't abort the solve.
+        let _ = render(p, &mut stdout.lock(), fps);
+        match fps {
+            Some(fps) => thread::sleep(Duration::from_secs(1) / fps.max(1)),
+            None => {
+                let _ = lines.next();
+            }
+        }
+    })
+}